@@ -1,3 +1,10 @@
+#![allow(
+    clippy::needless_return,
+    clippy::needless_arbitrary_self_type,
+    clippy::redundant_field_names,
+    clippy::vec_box
+)]
+
 mod re_math;
 use crate::re_math::advanced_type::{Addition, Formula, FormulaTypes, Negative};
 #[allow(unused_imports)]
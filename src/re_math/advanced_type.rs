@@ -1,5 +1,8 @@
 use crate::re_math::basic_type::Float;
 use crate::re_math::basic_type::Integer;
+use crate::re_math::encoding::{
+    write_varint, DecodeError, DecodeErrorKind, Encoding, Json, JsonValue, Reader, MAX_DEPTH,
+};
 
 #[derive(Clone)]
 #[allow(dead_code)]
@@ -48,13 +51,10 @@ impl Formula for Addition {
         let mut item_update_list: Vec<Vec<Box<FormulaTypes>>> = Vec::new();
         for i in 0..(self.elements.len()) {
             let element: Box<FormulaTypes> = self.elements[i].clone();
-            match (*element).clone() {
-                FormulaTypes::Addition(f) => {
-                    position_update_list.push((i as isize + delta_position) as usize);
-                    delta_position += f.elements.len() as isize - 1;
-                    item_update_list.push(f.elements);
-                }
-                _ => {}
+            if let FormulaTypes::Addition(f) = (*element).clone() {
+                position_update_list.push((i as isize + delta_position) as usize);
+                delta_position += f.elements.len() as isize - 1;
+                item_update_list.push(f.elements);
             }
         }
         return Box::new(FormulaTypes::Addition(self.clone()));
@@ -105,3 +105,121 @@ impl Formula for Negative {
         Float::opposite((*self.element).calculate())
     }
 }
+
+impl FormulaTypes {
+    fn decode_node(reader: &mut Reader, depth: usize) -> Result<Self, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(reader.error(DecodeErrorKind::TooDeep));
+        }
+        let position: usize = reader.position();
+        match reader.read_u8()? {
+            0 => Ok(Self::Integer(Integer::decode_body(reader)?)),
+            1 => Ok(Self::Float(Float::decode_body(reader)?)),
+            2 => {
+                // every element takes at least one byte
+                let count: u64 = reader.read_varint()?;
+                if count > reader.remaining() as u64 {
+                    return Err(DecodeError::new(
+                        DecodeErrorKind::UnexpectedEnd,
+                        reader.position() + reader.remaining(),
+                    ));
+                }
+                let mut elements: Vec<Box<FormulaTypes>> = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    elements.push(Box::new(Self::decode_node(reader, depth + 1)?));
+                }
+                Ok(Self::Addition(Addition::new(elements)))
+            }
+            3 => Ok(Self::Negative(Negative::new(Box::new(Self::decode_node(
+                reader,
+                depth + 1,
+            )?)))),
+            _ => Err(DecodeError::new(DecodeErrorKind::InvalidTag, position)),
+        }
+    }
+    fn from_json_node(json: &Json, depth: usize) -> Result<Self, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::new(DecodeErrorKind::TooDeep, json.position));
+        }
+        let node_type: &Json = json.get("type")?;
+        match node_type.as_str()? {
+            "integer" => Ok(Self::Integer(Integer::from_json_value(json.get("value")?)?)),
+            "float" => Ok(Self::Float(Float::from_json_value(json.get("value")?)?)),
+            "addition" => {
+                let mut elements: Vec<Box<FormulaTypes>> = Vec::new();
+                for element in json.get("elements")?.as_array()? {
+                    elements.push(Box::new(Self::from_json_node(element, depth + 1)?));
+                }
+                Ok(Self::Addition(Addition::new(elements)))
+            }
+            "negative" => Ok(Self::Negative(Negative::new(Box::new(
+                Self::from_json_node(json.get("element")?, depth + 1)?,
+            )))),
+            _ => Err(DecodeError::new(
+                DecodeErrorKind::InvalidTag,
+                node_type.position,
+            )),
+        }
+    }
+}
+impl Encoding for FormulaTypes {
+    const KIND: u8 = 2;
+    fn encode_body(self: &Self, out: &mut Vec<u8>) {
+        match self {
+            Self::Integer(n) => {
+                out.push(0);
+                n.encode_body(out);
+            }
+            Self::Float(n) => {
+                out.push(1);
+                n.encode_body(out);
+            }
+            Self::Addition(f) => {
+                out.push(2);
+                write_varint(out, f.elements.len() as u64);
+                for element in f.elements.iter() {
+                    element.encode_body(out);
+                }
+            }
+            Self::Negative(f) => {
+                out.push(3);
+                f.element.encode_body(out);
+            }
+        }
+    }
+    fn decode_body(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Self::decode_node(reader, 0)
+    }
+    fn to_json_value(self: &Self) -> Json {
+        let node_type = |name: &str| Json::string(name.to_string());
+        match self {
+            Self::Integer(n) => Json::object(vec![
+                ("type", node_type("integer")),
+                ("value", n.to_json_value()),
+            ]),
+            Self::Float(n) => Json::object(vec![
+                ("type", node_type("float")),
+                ("value", n.to_json_value()),
+            ]),
+            Self::Addition(f) => Json::object(vec![
+                ("type", node_type("addition")),
+                (
+                    "elements",
+                    Json::new(JsonValue::Array(
+                        f.elements
+                            .iter()
+                            .map(|element| element.to_json_value())
+                            .collect(),
+                    )),
+                ),
+            ]),
+            Self::Negative(f) => Json::object(vec![
+                ("type", node_type("negative")),
+                ("element", f.element.to_json_value()),
+            ]),
+        }
+    }
+    fn from_json_value(json: &Json) -> Result<Self, DecodeError> {
+        Self::from_json_node(json, 0)
+    }
+}
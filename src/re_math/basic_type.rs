@@ -1,82 +1,823 @@
 // 2024 (c) MaoHuPi
 // RuNeX/src/re_math/basic_type.rs
 
+use std::cell::Cell;
 use std::char::from_digit;
 use std::cmp::{max, min, Ordering};
-use std::usize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Mul, MulAssign,
+    Neg, Not, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
+};
+use std::rc::Rc;
+use std::str::FromStr;
 
-#[derive(Clone)]
+use crate::re_math::encoding::{
+    write_varint, DecodeError, DecodeErrorKind, Encoding, Json, Reader,
+};
+
+type Digit = u32;
+type DoubleDigit = u64;
+const DIGIT_BITS: u32 = Digit::BITS;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Integer {
     sign: bool,
-    digits: Vec<u8>,
+    digits: Vec<Digit>,
+}
+
+const LIMB_RADIX: DoubleDigit = 1 << DIGIT_BITS;
+pub const DECIMAL_RADIX: usize = 10;
+// the largest power of ten that fits in one digit
+const DECIMAL_CHUNK_RADIX: Digit = 1_000_000_000;
+const DECIMAL_CHUNK_WIDTH: usize = 9;
+// below these sizes (in digits) the quadratic algorithms win
+const KARATSUBA_THRESHOLD: usize = 32;
+const RADIX_CONVERSION_THRESHOLD: usize = 32;
+const DIVISION_THRESHOLD: usize = 48;
+// factor() tries every prime below this before pollard's rho
+const TRIAL_DIVISION_LIMIT: u32 = 10_000;
+// from these sizes (in digits of the shorter factor) on, the next multiplication wins
+const TOOM_3_THRESHOLD: usize = 256;
+const NTT_THRESHOLD: usize = 2048;
+// p = k * 2^n + 1 with primitive root 3, the smallest 2^n (2^23) bounds the transform length
+const NTT_PRIMES: [u64; 3] = [998_244_353, 167_772_161, 469_762_049];
+const NTT_MAX_DIGITS: usize = 1 << 22;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithmeticError {
+    DivisionByZero,
+    NegativeExponent,
+    NotInvertible,
+    NegativeRoot,
+    NonPositiveLogarithm,
+    Overflow,
+}
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::NegativeExponent => write!(f, "negative exponent"),
+            Self::NotInvertible => write!(f, "value is not invertible for this modulus"),
+            Self::NegativeRoot => write!(f, "even root of a negative number"),
+            Self::NonPositiveLogarithm => write!(f, "logarithm of a non-positive number"),
+            Self::Overflow => write!(f, "result too long to write out"),
+        }
+    }
+}
+impl std::error::Error for ArithmeticError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseNumberErrorKind {
+    Empty,        // no digits where some were expected
+    InvalidDigit, // a character that does not belong here
+    OutOfRange,   // an exponent too large for a Float
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseNumberError {
+    kind: ParseNumberErrorKind,
+    position: usize, // byte offset into the input
+}
+impl ParseNumberError {
+    fn new(kind: ParseNumberErrorKind, position: usize) -> Self {
+        Self {
+            kind: kind,
+            position: position,
+        }
+    }
+    pub fn kind(self: &Self) -> ParseNumberErrorKind {
+        self.kind
+    }
+    pub fn position(self: &Self) -> usize {
+        self.position
+    }
+}
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseNumberErrorKind::Empty => write!(f, "missing digits at byte {}", self.position),
+            ParseNumberErrorKind::InvalidDigit => {
+                write!(f, "invalid character at byte {}", self.position)
+            }
+            ParseNumberErrorKind::OutOfRange => {
+                write!(f, "exponent out of range at byte {}", self.position)
+            }
+        }
+    }
+}
+impl std::error::Error for ParseNumberError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TryFromIntegerError;
+impl fmt::Display for TryFromIntegerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "integer out of range for the target type")
+    }
+}
+impl std::error::Error for TryFromIntegerError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TryFromFloatError;
+impl fmt::Display for TryFromFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NaN and infinity have no Float value")
+    }
+}
+impl std::error::Error for TryFromFloatError {}
+
+fn parse_sign(bytes: &[u8], position: usize) -> (bool, usize) {
+    // (sign, position after it), true: (-), false: (+)
+    match bytes.get(position) {
+        Some(b'-') => (true, position + 1),
+        Some(b'+') => (false, position + 1),
+        _ => (false, position),
+    }
+}
+fn parse_radix_prefix(bytes: &[u8], position: usize) -> (u32, usize) {
+    // (radix, position after the prefix) for 0x, 0o and 0b
+    if bytes.get(position) == Some(&b'0') {
+        match bytes.get(position + 1) {
+            Some(b'x' | b'X') => return (16, position + 2),
+            Some(b'o' | b'O') => return (8, position + 2),
+            Some(b'b' | b'B') => return (2, position + 2),
+            _ => {}
+        }
+    }
+    (DECIMAL_RADIX as u32, position)
+}
+fn parse_digits(
+    bytes: &[u8],
+    position: usize,
+    radix: u32,
+) -> Result<(Vec<u8>, usize), ParseNumberError> {
+    // ([..., n*radix^1, n*radix^0], position after the last digit)
+    // a '_' separator is only allowed between two digits
+    let mut digits: Vec<u8> = Vec::new();
+    let mut position: usize = position;
+    while position < bytes.len() {
+        let c: u8 = bytes[position];
+        if c == b'_' {
+            let next_is_digit: bool = bytes
+                .get(position + 1)
+                .is_some_and(|&next| (next as char).is_digit(radix));
+            if digits.is_empty() || !next_is_digit {
+                return Err(ParseNumberError::new(
+                    ParseNumberErrorKind::InvalidDigit,
+                    position,
+                ));
+            }
+        } else {
+            match (c as char).to_digit(radix) {
+                Some(digit) => digits.push(digit as u8),
+                None => break,
+            }
+        }
+        position += 1;
+    }
+    Ok((digits, position))
+}
+
+fn pow_mod_u64(base: u64, exp: u64, modulus: u64) -> u64 {
+    // modulus < 2^32
+    let mut result: u64 = 1;
+    let mut base: u64 = base % modulus;
+    let mut exp: u64 = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result
+}
+
+fn ntt(values: &mut [u64], modulus: u64, inverse: bool) {
+    // in-place iterative number theoretic transform, values.len() is a power of two
+    let length: usize = values.len();
+    let mut j: usize = 0;
+    for i in 1..length {
+        let mut bit: usize = length >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+    let mut width: usize = 2;
+    while width <= length {
+        let mut root: u64 = pow_mod_u64(3, (modulus - 1) / width as u64, modulus);
+        if inverse {
+            root = pow_mod_u64(root, modulus - 2, modulus);
+        }
+        let half: usize = width / 2;
+        let mut roots: Vec<u64> = Vec::with_capacity(half);
+        let mut w: u64 = 1;
+        for _ in 0..half {
+            roots.push(w);
+            w = w * root % modulus;
+        }
+        for block in values.chunks_mut(width) {
+            let (low, high) = block.split_at_mut(half);
+            for ((x, y), &w) in low.iter_mut().zip(high.iter_mut()).zip(roots.iter()) {
+                let u: u64 = *x;
+                let v: u64 = *y * w % modulus;
+                *x = if u + v >= modulus {
+                    u + v - modulus
+                } else {
+                    u + v
+                };
+                *y = if u >= v { u - v } else { u + modulus - v };
+            }
+        }
+        width <<= 1;
+    }
+    if inverse {
+        let length_inverse: u64 = pow_mod_u64(length as u64, modulus - 2, modulus);
+        for value in values.iter_mut() {
+            *value = *value * length_inverse % modulus;
+        }
+    }
+}
+
+fn small_primes(limit: u32) -> Vec<u32> {
+    // sieve of eratosthenes, primes below limit
+    let mut is_composite: Vec<bool> = vec![false; limit as usize];
+    let mut primes: Vec<u32> = Vec::new();
+    for i in 2..limit as usize {
+        if is_composite[i] {
+            continue;
+        }
+        primes.push(i as u32);
+        for multiple in (i * i..limit as usize).step_by(i) {
+            is_composite[multiple] = true;
+        }
+    }
+    primes
+}
+
+fn is_prime_u64(n: u64) -> bool {
+    // miller-rabin, these bases are deterministic for every n < 3.3 * 10^24
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    let mul_mod = |a: u64, b: u64| -> u64 { (a as u128 * b as u128 % n as u128) as u64 };
+    if n < 2 {
+        return false;
+    }
+    for &base in BASES.iter() {
+        if n.is_multiple_of(base) {
+            return n == base;
+        }
+    }
+    let s: u32 = (n - 1).trailing_zeros();
+    let d: u64 = (n - 1) >> s;
+    'bases: for &base in BASES.iter() {
+        // x = base^d mod n
+        let mut x: u64 = 1;
+        let mut power: u64 = base;
+        let mut exp: u64 = d;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                x = mul_mod(x, power);
+            }
+            power = mul_mod(power, power);
+            exp >>= 1;
+        }
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x);
+            if x == n - 1 {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
 }
 
-pub const BASIC_INT_RADIX: usize = 10;
+// lhs (op) rhs for every owned / borrowed combination, plus lhs (op)= rhs,
+// all forwarded to the associated function of the same name (or to $function)
+macro_rules! forward_binary_operator {
+    ($type:ident, $op_trait:ident, $op:ident, $assign_trait:ident, $assign_op:ident) => {
+        forward_binary_operator!($type, $op_trait, $op, $assign_trait, $assign_op, $op);
+    };
+    (
+        $type:ident,
+        $op_trait:ident,
+        $op:ident,
+        $assign_trait:ident,
+        $assign_op:ident,
+        $function:ident
+    ) => {
+        impl $op_trait for $type {
+            type Output = $type;
+            fn $op(self, rhs: $type) -> $type {
+                $type::$function(self, rhs)
+            }
+        }
+        impl $op_trait<&$type> for $type {
+            type Output = $type;
+            fn $op(self, rhs: &$type) -> $type {
+                $type::$function(self, rhs.clone())
+            }
+        }
+        impl $op_trait<$type> for &$type {
+            type Output = $type;
+            fn $op(self, rhs: $type) -> $type {
+                $type::$function(self.clone(), rhs)
+            }
+        }
+        impl $op_trait<&$type> for &$type {
+            type Output = $type;
+            fn $op(self, rhs: &$type) -> $type {
+                $type::$function(self.clone(), rhs.clone())
+            }
+        }
+        impl $assign_trait for $type {
+            fn $assign_op(&mut self, rhs: $type) {
+                *self = $type::$function(std::mem::replace(self, $type::zero()), rhs);
+            }
+        }
+        impl $assign_trait<&$type> for $type {
+            fn $assign_op(&mut self, rhs: &$type) {
+                *self = $type::$function(std::mem::replace(self, $type::zero()), rhs.clone());
+            }
+        }
+    };
+}
+// like forward_binary_operator!, but an owned lhs is updated in place by the
+// associated $assign_op(&mut lhs, &rhs), so its buffer is reused
+macro_rules! forward_in_place_operator {
+    ($type:ident, $op_trait:ident, $op:ident, $assign_trait:ident, $assign_op:ident) => {
+        impl $op_trait for $type {
+            type Output = $type;
+            fn $op(mut self, rhs: $type) -> $type {
+                $type::$assign_op(&mut self, &rhs);
+                self
+            }
+        }
+        impl $op_trait<&$type> for $type {
+            type Output = $type;
+            fn $op(mut self, rhs: &$type) -> $type {
+                $type::$assign_op(&mut self, rhs);
+                self
+            }
+        }
+        impl $op_trait<$type> for &$type {
+            type Output = $type;
+            fn $op(self, rhs: $type) -> $type {
+                $type::$op(self.clone(), rhs)
+            }
+        }
+        impl $op_trait<&$type> for &$type {
+            type Output = $type;
+            fn $op(self, rhs: &$type) -> $type {
+                let mut result: $type = self.clone();
+                $type::$assign_op(&mut result, rhs);
+                result
+            }
+        }
+        impl $assign_trait for $type {
+            fn $assign_op(&mut self, rhs: $type) {
+                $type::$assign_op(self, &rhs);
+            }
+        }
+        impl $assign_trait<&$type> for $type {
+            fn $assign_op(&mut self, rhs: &$type) {
+                $type::$assign_op(self, rhs);
+            }
+        }
+    };
+}
+macro_rules! forward_negative_operator {
+    ($type:ident) => {
+        impl Neg for $type {
+            type Output = $type;
+            fn neg(self) -> $type {
+                $type::opposite(self)
+            }
+        }
+        impl Neg for &$type {
+            type Output = $type;
+            fn neg(self) -> $type {
+                $type::opposite(self.clone())
+            }
+        }
+    };
+}
 
 #[allow(dead_code)]
 impl Integer {
     pub fn new(sign: bool, digits: Vec<u8>) -> Self {
-        Self {
+        // digits: decimal digits, [n*1, n*1e1, n*1e2, n*1e3, ...]
+        let mut decimal: Vec<u8> = digits;
+        decimal.reverse();
+        let mut result: Self = Self::digits_from_radix(&decimal, DECIMAL_RADIX as u32);
+        result.sign = sign;
+        result.tidy();
+        result.validate();
+        result
+    }
+    fn from_digits(sign: bool, digits: Vec<Digit>) -> Self {
+        let mut result: Self = Self {
             sign: sign,     // true: (-), false: (+)
-            digits: digits, // [n*1, n*1e1, n*1e2, n*1e3, ...]
-        }
+            digits: digits, // [n*1, n*2^32, n*2^64, n*2^96, ...]
+        };
+        result.tidy();
+        result
     }
     pub fn zero() -> Self {
-        Self::new(false, vec![0])
+        Self::from_digits(false, vec![0])
+    }
+    pub fn one() -> Self {
+        Self::from_digits(false, vec![1])
     }
 
     pub fn from_isize(number: isize) -> Self {
-        let mut number: isize = number;
-        const RADIX: isize = BASIC_INT_RADIX as isize;
-        let mut digits: Vec<u8> = Vec::new();
-        while number > RADIX {
-            let digit: isize = number % RADIX;
-            number -= digit;
-            number /= RADIX;
-            digits.push(digit as u8);
-        }
-        digits.push(number as u8);
-        Self::new(number < 0, digits)
-    }
-    pub fn from_string(string: String) -> Self {
-        let mut sign: bool = false;
-        let mut chars: Vec<char> = string.chars().collect();
-        if chars[0] == '-' {
-            chars = chars[1..].to_vec();
-            sign = true;
-        }
-        let mut digits: Vec<u8> = chars
-            .iter()
-            .map(|c| c.to_digit(BASIC_INT_RADIX as u32).unwrap() as u8)
-            .collect();
-        digits.reverse();
-        Self::new(sign, digits)
+        Self::from(number)
     }
-    pub fn to_isize(self: Self) -> isize {
-        let digits: Vec<u8> = self.digits.clone();
-        digits
-            .iter()
-            .enumerate()
-            .map(|(i, &n)| (n as usize) * BASIC_INT_RADIX.pow(i as u32))
-            .sum::<usize>() as isize
-            * (if self.sign { -1 } else { 1 })
+    fn from_u128(sign: bool, magnitude: u128) -> Self {
+        let mut magnitude: u128 = magnitude;
+        let mut digits: Vec<Digit> = Vec::new();
+        while magnitude > 0 {
+            digits.push(magnitude as Digit);
+            magnitude >>= DIGIT_BITS;
+        }
+        Self::from_digits(sign, digits)
     }
-    pub fn to_string(self: Self) -> String {
-        let mut digits: Vec<u8> = self.digits.clone();
-        digits.reverse();
+    pub fn from_string(string: String) -> Result<Self, ParseNumberError> {
+        Self::parse(&string)
+    }
+    fn parse(string: &str) -> Result<Self, ParseNumberError> {
+        // [+-][0x|0o|0b]digits, digits may be separated by '_'
+        let bytes: &[u8] = string.as_bytes();
+        let (sign, position) = parse_sign(bytes, 0);
+        let (radix, position) = parse_radix_prefix(bytes, position);
+        let (digits, end) = parse_digits(bytes, position, radix)?;
+        if end < bytes.len() {
+            return Err(ParseNumberError::new(
+                ParseNumberErrorKind::InvalidDigit,
+                end,
+            ));
+        }
+        if digits.is_empty() {
+            return Err(ParseNumberError::new(ParseNumberErrorKind::Empty, end));
+        }
+        let mut result: Self = Self::digits_from_radix(&digits, radix);
+        result.sign = sign;
+        result.tidy();
+        Ok(result)
+    }
+    pub fn to_string_radix(self: &Self, radix: u32) -> String {
+        // radix: 2..=36, digits above 9 are written as lowercase letters
+        assert!(
+            (2..=36).contains(&radix),
+            "Integer::to_string_radix: radix must be in 2..=36"
+        );
         (if self.sign { "-" } else { "" }).to_string()
-            + digits
+            + Self::digits_to_radix(self, radix)
                 .iter()
-                .map(|&n| from_digit(n as u32, 16).unwrap())
+                .map(|&n| from_digit(n as u32, radix).unwrap())
                 .collect::<String>()
                 .as_str()
     }
+    pub fn from_str_radix(string: &str, radix: u32) -> Result<Self, ParseNumberError> {
+        // [+-]digits, digits may be separated by '_', letters in either case
+        assert!(
+            (2..=36).contains(&radix),
+            "Integer::from_str_radix: radix must be in 2..=36"
+        );
+        let bytes: &[u8] = string.as_bytes();
+        let (sign, position) = parse_sign(bytes, 0);
+        let (digits, end) = parse_digits(bytes, position, radix)?;
+        if end < bytes.len() {
+            return Err(ParseNumberError::new(
+                ParseNumberErrorKind::InvalidDigit,
+                end,
+            ));
+        }
+        if digits.is_empty() {
+            return Err(ParseNumberError::new(ParseNumberErrorKind::Empty, end));
+        }
+        let mut result: Self = Self::digits_from_radix(&digits, radix);
+        result.sign = sign && !result.is_digits_zero();
+        result.validate();
+        Ok(result)
+    }
+    pub fn to_radix_digits(n: Self, radix: Self) -> (bool, Vec<Self>) {
+        // (sign, [n*1, n*radix, n*radix^2, ...]) for any radix >= 2
+        assert!(
+            !radix.sign && Self::digits_cmp(&radix, &Self::one()) == Ordering::Greater,
+            "Integer::to_radix_digits: radix must be at least 2"
+        );
+        let mut digits: Vec<Self> = Self::digits_to_radix_chunks(&n, radix);
+        if digits.is_empty() {
+            digits.push(Self::zero());
+        }
+        digits.reverse();
+        (n.sign, digits)
+    }
+    pub fn from_radix_digits(sign: bool, digits: Vec<Self>, radix: Self) -> Self {
+        // digits: [n*1, n*radix, n*radix^2, ...], each one in [0, radix)
+        assert!(
+            !radix.sign && Self::digits_cmp(&radix, &Self::one()) == Ordering::Greater,
+            "Integer::from_radix_digits: radix must be at least 2"
+        );
+        debug_assert!(
+            digits
+                .iter()
+                .all(|digit| !digit.sign && Self::digits_cmp(digit, &radix) == Ordering::Less),
+            "Integer::from_radix_digits: digit out of range"
+        );
+        let mut digits: Vec<Self> = digits;
+        digits.reverse();
+        let mut result: Self = Self::digits_from_radix_chunks(&digits, radix);
+        result.sign = sign && !result.is_digits_zero();
+        result.validate();
+        result
+    }
+    pub fn to_isize(self: Self) -> isize {
+        // panics when out of range, use isize::try_from to handle that case
+        isize::try_from(&self).expect("Integer::to_isize: value out of range")
+    }
+    fn to_u128_magnitude(self: &Self) -> Option<u128> {
+        if self.digits.len() * DIGIT_BITS as usize > u128::BITS as usize {
+            return None;
+        }
+        Some(
+            self.digits
+                .iter()
+                .rev()
+                .fold(0, |value, &digit| (value << DIGIT_BITS) | digit as u128),
+        )
+    }
+    pub fn to_f64(self: &Self) -> f64 {
+        // round half to even, overflows to infinity
+        let bits: usize = self.bit_length();
+        let magnitude: f64 = if bits <= u64::BITS as usize {
+            self.digits
+                .iter()
+                .rev()
+                .fold(0_u64, |value, &digit| (value << DIGIT_BITS) | digit as u64)
+                as f64
+        } else if bits > f64::MAX_EXP as usize {
+            f64::INFINITY
+        } else {
+            // keep the top 64 bits, any lower bit that is set only acts as a sticky bit
+            let shift: usize = bits - u64::BITS as usize;
+            let top: Self = self.digits_shr_bits(shift);
+            let mut top_bits: u64 = ((top.digits[1] as u64) << DIGIT_BITS) | top.digits[0] as u64;
+            if self.trailing_zeros().unwrap() < shift {
+                top_bits |= 1;
+            }
+            // 2^shift is exact, so the product only rounds on overflow
+            (top_bits as f64) * f64::from_bits(((f64::MAX_EXP as u64 - 1) + shift as u64) << 52)
+        };
+        if self.sign {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+    fn decimal_digits(self: &Self) -> Vec<u8> {
+        // [n*1, n*1e1, n*1e2, n*1e3, ...], magnitude only
+        let mut decimal: Vec<u8> = Self::digits_to_radix(self, DECIMAL_RADIX as u32);
+        decimal.reverse();
+        decimal
+    }
+
+    fn decimal_length(self: &Self) -> usize {
+        // decimal digits of |n|, 1 for 0; the bit length bounds it to two candidates
+        let bits: usize = self.bit_length();
+        if bits == 0 {
+            return 1;
+        }
+        let mut length: usize =
+            ((bits as u128 - 1) * 301_029_995_663_981 / 1_000_000_000_000_000) as usize + 1;
+        while Self::digits_cmp(self, &Self::pow10(length)) != Ordering::Less {
+            length += 1;
+        }
+        length
+    }
+
+    fn digits_mul_add_small(self: &mut Self, factor: Digit, addend: Digit) {
+        let mut carry: DoubleDigit = addend as DoubleDigit;
+        for digit in self.digits.iter_mut() {
+            let current: DoubleDigit = *digit as DoubleDigit * factor as DoubleDigit + carry;
+            *digit = current as Digit;
+            carry = current >> DIGIT_BITS;
+        }
+        if carry > 0 {
+            self.digits.push(carry as Digit);
+        }
+    }
+    fn radix_chunk(radix: u32) -> (Digit, usize) {
+        // (radix^width, width) for the largest width that still fits into one digit
+        let mut chunk_radix: Digit = radix;
+        let mut chunk_width: usize = 1;
+        while let Some(next) = chunk_radix.checked_mul(radix) {
+            chunk_radix = next;
+            chunk_width += 1;
+        }
+        (chunk_radix, chunk_width)
+    }
+    fn digits_from_radix(values: &[u8], radix: u32) -> Self {
+        // values: [..., n*radix^2, n*radix^1, n*radix^0], magnitude only
+        if radix.is_power_of_two() {
+            let bits: usize = radix.trailing_zeros() as usize;
+            let mut new_digits: Vec<Digit> =
+                vec![0; (values.len() * bits).div_ceil(DIGIT_BITS as usize) + 1];
+            for (i, &value) in values.iter().rev().enumerate() {
+                let index: usize = i * bits / DIGIT_BITS as usize;
+                let offset: usize = i * bits % DIGIT_BITS as usize;
+                new_digits[index] |= (value as Digit) << offset;
+                if offset + bits > DIGIT_BITS as usize {
+                    new_digits[index + 1] |= (value as Digit) >> (DIGIT_BITS as usize - offset);
+                }
+            }
+            return Self::from_digits(false, new_digits);
+        }
+        // group the values into chunks, aligned at the low end
+        let (chunk_radix, chunk_width) = Self::radix_chunk(radix);
+        let mut chunks: Vec<Self> = Vec::with_capacity(values.len() / chunk_width + 1);
+        let mut start: usize = 0;
+        let mut end: usize = match values.len() % chunk_width {
+            0 => min(chunk_width, values.len()),
+            head_width => head_width,
+        };
+        while start < end {
+            let chunk: Digit = values[start..end]
+                .iter()
+                .fold(0, |value, &n| value * radix + n as Digit);
+            chunks.push(Self::from_digits(false, vec![chunk]));
+            start = end;
+            end = min(end + chunk_width, values.len());
+        }
+        Self::digits_from_radix_chunks(&chunks, Self::from_digits(false, vec![chunk_radix]))
+    }
+    fn digits_from_radix_chunks(chunks: &[Self], radix: Self) -> Self {
+        // chunks: [..., n*radix^2, n*radix^1, n*radix^0], each one below radix
+        // powers[j] = radix^(2^j)
+        let mut powers: Vec<Self> = vec![radix];
+        while 1 << powers.len() < chunks.len() {
+            let last: Self = powers[powers.len() - 1].clone();
+            powers.push(Self::digits_mul(last.clone(), last, 0));
+        }
+        Self::digits_from_radix_split(chunks, &powers)
+    }
+    fn digits_from_radix_split(chunks: &[Self], powers: &[Self]) -> Self {
+        if chunks.len() <= RADIX_CONVERSION_THRESHOLD {
+            let radix: &Self = &powers[0];
+            let mut result: Self = Self::zero();
+            for chunk in chunks {
+                if radix.digits.len() == 1 && chunk.digits.len() == 1 {
+                    result.digits_mul_add_small(radix.digits[0], chunk.digits[0]);
+                } else {
+                    result =
+                        Self::digits_add(Self::digits_mul(result, radix.clone(), 0), chunk.clone());
+                }
+            }
+            result.tidy();
+            return result;
+        }
+        // high * radix^(2^j) + low
+        let mut j: usize = powers.len() - 1;
+        while 1 << j >= chunks.len() {
+            j -= 1;
+        }
+        let split: usize = chunks.len() - (1 << j);
+        let high: Self = Self::digits_from_radix_split(&chunks[..split], &powers[..=j]);
+        let low: Self = Self::digits_from_radix_split(&chunks[split..], &powers[..=j]);
+        Self::digits_add(Self::digits_mul(high, powers[j].clone(), 0), low)
+    }
+    fn digits_to_radix(n: &Self, radix: u32) -> Vec<u8> {
+        // [..., n*radix^2, n*radix^1, n*radix^0], magnitude only
+        if radix.is_power_of_two() {
+            let bits: usize = radix.trailing_zeros() as usize;
+            let count: usize = max(n.bit_length().div_ceil(bits), 1);
+            return (0..count)
+                .rev()
+                .map(|i| {
+                    let index: usize = i * bits / DIGIT_BITS as usize;
+                    let offset: usize = i * bits % DIGIT_BITS as usize;
+                    let mut value: Digit = n.digits[index] >> offset;
+                    if offset + bits > DIGIT_BITS as usize && index + 1 < n.digits.len() {
+                        value |= n.digits[index + 1] << (DIGIT_BITS as usize - offset);
+                    }
+                    (value & (radix - 1)) as u8
+                })
+                .collect();
+        }
+        let (chunk_radix, chunk_width) = Self::radix_chunk(radix);
+        let chunks: Vec<Self> =
+            Self::digits_to_radix_chunks(n, Self::from_digits(false, vec![chunk_radix]));
+        let mut values: Vec<u8> = Vec::with_capacity(chunks.len() * chunk_width);
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mut chunk_value: Digit = chunk.digits[0];
+            let mut chunk_values: Vec<u8> = Vec::with_capacity(chunk_width);
+            for _ in 0..chunk_width {
+                chunk_values.push((chunk_value % radix) as u8);
+                chunk_value /= radix;
+            }
+            if i == 0 {
+                while chunk_values.last() == Some(&0) {
+                    chunk_values.pop();
+                }
+            }
+            values.extend(chunk_values.iter().rev());
+        }
+        if values.is_empty() {
+            values.push(0);
+        }
+        values
+    }
+    fn digits_to_radix_chunks(n: &Self, radix: Self) -> Vec<Self> {
+        // [..., n*radix^2, n*radix^1, n*radix^0] without leading zero chunks, magnitude only
+        // powers[j] = radix^(2^j), until the last one squared is larger than n
+        let mut powers: Vec<Self> = vec![radix];
+        while 2 * (powers[powers.len() - 1].digits.len() - 1) < n.digits.len() {
+            let last: Self = powers[powers.len() - 1].clone();
+            powers.push(Self::digits_mul(last.clone(), last, 0));
+        }
+        let mut chunks: Vec<Self> = Vec::new();
+        Self::digits_to_radix_split(
+            Self::from_digits(false, n.digits.clone()),
+            &powers,
+            &mut chunks,
+            0,
+        );
+        chunks
+    }
+    fn digits_to_radix_split(n: Self, powers: &[Self], chunks: &mut Vec<Self>, width: usize) {
+        // n < powers[powers.len() - 1]^2, the output is padded with zero chunks to `width`
+        if n.digits.len() <= RADIX_CONVERSION_THRESHOLD || powers.len() <= 1 {
+            let radix: &Self = &powers[0];
+            let mut low_chunks: Vec<Self> = Vec::new();
+            let mut rest: Self = n;
+            while !rest.is_digits_zero() {
+                let (quotient, chunk) = if radix.digits.len() == 1 {
+                    let (quotient, chunk) = Self::digits_short_div(rest, radix.digits[0]);
+                    (quotient, Self::from_digits(false, vec![chunk]))
+                } else {
+                    Self::digits_div_rem(rest, radix.clone())
+                };
+                low_chunks.push(chunk);
+                rest = quotient;
+            }
+            while low_chunks.len() < width {
+                low_chunks.push(Self::zero());
+            }
+            chunks.extend(low_chunks.into_iter().rev());
+            return;
+        }
+        let j: usize = powers.len() - 1;
+        let low_width: usize = 1 << j;
+        let (high, low) = Self::digits_div_rem(n, powers[j].clone());
+        if high.is_digits_zero() {
+            Self::digits_to_radix_split(low, &powers[..j], chunks, width);
+        } else {
+            Self::digits_to_radix_split(
+                high,
+                &powers[..j],
+                chunks,
+                width.saturating_sub(low_width),
+            );
+            Self::digits_to_radix_split(low, &powers[..j], chunks, low_width);
+        }
+    }
+    fn pow10(exp: usize) -> Self {
+        let mut result: Self = Self::from_digits(
+            false,
+            vec![(DECIMAL_RADIX as Digit).pow((exp % DECIMAL_CHUNK_WIDTH) as u32)],
+        );
+        let mut base: Self = Self::from_digits(false, vec![DECIMAL_CHUNK_RADIX]);
+        let mut exp: usize = exp / DECIMAL_CHUNK_WIDTH;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = Self::digits_mul(result, base.clone(), 0);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = Self::digits_mul(base.clone(), base, 0);
+            }
+        }
+        result
+    }
 
     pub fn opposite(n: Self) -> Self {
-        Self::new(!n.sign, n.digits)
+        let result: Self = Self::from_digits(!n.sign, n.digits);
+        result.validate();
+        result
     }
 
-    fn digits_cmp(a: Self, b: Self) -> Ordering {
+    fn digits_cmp(a: &Self, b: &Self) -> Ordering {
         let a_len: usize = a.digits.len();
         let b_len: usize = b.digits.len();
         if a_len > b_len {
@@ -95,6 +836,9 @@ impl Integer {
         }
     }
     pub fn cmp(a: Self, b: Self) -> Ordering {
+        Self::cmp_ref(&a, &b)
+    }
+    pub fn cmp_ref(a: &Self, b: &Self) -> Ordering {
         if !a.sign && b.sign {
             // a: (+), b: (-)
             return Ordering::Greater;
@@ -106,205 +850,274 @@ impl Integer {
             return Self::digits_cmp(a, b);
         } else {
             // a: (-), b: (-)
-            return match Self::digits_cmp(a, b) {
-                Ordering::Greater => Ordering::Less,
-                Ordering::Less => Ordering::Greater,
-                Ordering::Equal => Ordering::Equal,
-            };
+            return Self::digits_cmp(b, a);
         }
     }
+    pub fn cmp_abs(a: &Self, b: &Self) -> Ordering {
+        Self::digits_cmp(a, b)
+    }
 
-    fn tidy(self: &mut Self) {
-        let digits_len: usize = self.digits.len();
-        let mut zero_count: usize = 0;
-        const RADIX: u8 = BASIC_INT_RADIX as u8;
-        for i in 0..digits_len {
-            if self.digits[i] == 0 {
-                zero_count += 1;
-            } else {
-                if self.digits[i] >= RADIX {
-                    let digit: u8 = self.digits[i];
-                    self.digits[i] = digit % RADIX;
-                    self.digits[i + 1] += (digit - self.digits[i]) / RADIX;
-                }
-                zero_count = 0;
-            }
+    pub fn validate(self: &Self) {
+        // the canonical form every public operation returns:
+        // no leading zero digits, zero is exactly [0], and zero is never negative
+        debug_assert!(!self.digits.is_empty(), "Integer: no digits");
+        debug_assert!(
+            self.digits.len() == 1 || self.digits[self.digits.len() - 1] != 0,
+            "Integer: leading zero digits"
+        );
+        debug_assert!(!(self.sign && self.is_zero()), "Integer: negative zero");
+    }
+    pub fn is_zero(self: &Self) -> bool {
+        self.digits == [0]
+    }
+    pub fn is_one(self: &Self) -> bool {
+        !self.sign && self.digits == [1]
+    }
+    pub fn is_even(self: &Self) -> bool {
+        self.digits[0] & 1 == 0
+    }
+    pub fn signum(self: &Self) -> Self {
+        // -1, 0 or 1
+        if self.is_zero() {
+            Self::zero()
+        } else {
+            Self::from_digits(self.sign, vec![1])
         }
-        if zero_count > 0 {
-            self.digits = self.digits[..(self.digits.len() - zero_count)].to_vec();
+    }
+    pub fn abs(n: Self) -> Self {
+        Self::from_digits(false, n.digits)
+    }
+
+    fn tidy(self: &mut Self) {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
         }
-        if self.digits.len() == 0 {
+        if self.digits.is_empty() {
             self.sign = false;
-            self.digits = vec![0];
+            self.digits.push(0);
         }
     }
     fn digits_add(a: Self, b: Self) -> Self {
-        let mut _min_digits_number: usize = a.digits.len();
-        let mut _max_digits_number: usize = b.digits.len();
-        let mut max_digits_name: char = 'b';
-        if _min_digits_number > _max_digits_number {
-            let temp: usize = _min_digits_number;
-            _min_digits_number = _max_digits_number;
-            _max_digits_number = temp;
-            max_digits_name = 'a';
-        }
-        let mut carry: u8 = 0;
-        let mut new_digits: Vec<u8> = Vec::new();
-        for i in 0.._max_digits_number {
-            let mut digit: u8;
-            if i < _min_digits_number {
-                digit = a.digits[i] + b.digits[i] + carry;
-            } else {
-                digit = (if max_digits_name == 'a' {
-                    a.digits[i]
+        let (long, short) = if a.digits.len() >= b.digits.len() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let mut carry: DoubleDigit = 0;
+        let mut new_digits: Vec<Digit> = Vec::with_capacity(long.digits.len() + 1);
+        for i in 0..long.digits.len() {
+            let digit: DoubleDigit = long.digits[i] as DoubleDigit
+                + (if i < short.digits.len() {
+                    short.digits[i]
                 } else {
-                    b.digits[i]
-                }) + carry;
-            }
-            if digit >= BASIC_INT_RADIX as u8 {
-                carry = 1;
-                digit = digit - BASIC_INT_RADIX as u8;
-            } else {
-                carry = 0;
-            }
-            new_digits.push(digit);
+                    0
+                }) as DoubleDigit
+                + carry;
+            new_digits.push(digit as Digit);
+            carry = digit >> DIGIT_BITS;
         }
         if carry > 0 {
-            new_digits.push(carry);
+            new_digits.push(carry as Digit);
         }
-        Self::new(false, new_digits)
+        Self::from_digits(false, new_digits)
     }
     fn digits_sub(big: Self, small: Self) -> Self {
-        let _max_digits_number: usize = big.digits.len();
-        let _min_digits_number: usize = small.digits.len();
-
-        let mut carry: u8 = 0;
-        let mut new_digits: Vec<u8> = Vec::new();
-        let mut zero_count: usize = 0;
-        for i in 0.._max_digits_number {
-            let mut digit: i8;
-            if i < _min_digits_number {
-                carry += small.digits[i]
-            }
-            digit = big.digits[i] as i8;
-            digit -= carry as i8;
-            if digit < 0 {
-                carry = 1;
-                digit += BASIC_INT_RADIX as i8;
-            } else {
-                carry = 0;
-            }
-            if digit == 0 {
-                zero_count += 1;
+        // |big| >= |small|
+        let mut borrow: bool = false;
+        let mut new_digits: Vec<Digit> = Vec::with_capacity(big.digits.len());
+        for i in 0..big.digits.len() {
+            let (digit, borrow_1) = big.digits[i].overflowing_sub(if i < small.digits.len() {
+                small.digits[i]
             } else {
-                zero_count = 0;
-            }
-            new_digits.push(digit as u8);
-        }
-        if zero_count > 0 {
-            new_digits = new_digits[..(new_digits.len() - zero_count)].to_vec();
+                0
+            });
+            let (digit, borrow_2) = digit.overflowing_sub(borrow as Digit);
+            borrow = borrow_1 || borrow_2;
+            new_digits.push(digit);
         }
-        Self::new(false, new_digits)
+        Self::from_digits(false, new_digits)
     }
-    pub fn add(a: Self, b: Self) -> Self {
-        let a_sign: bool = a.sign;
-        let b_sign: bool = b.sign;
-        if a_sign == b_sign {
-            let mut digits_result: Self = Self::digits_add(a, b);
-            digits_result.sign = a_sign;
-            return digits_result;
-        } else {
-            let new_sign: bool;
-            let big: Self;
-            let small: Self;
-            match Self::digits_cmp(a.clone(), b.clone()) {
-                Ordering::Greater => {
-                    new_sign = a_sign;
-                    big = a;
-                    small = b;
+    fn add_signed_assign(self: &mut Self, rhs: &Self, rhs_sign: bool) {
+        // self += |rhs| with the sign rhs_sign, reusing the digits of self
+        if rhs.is_digits_zero() {
+            return;
+        }
+        if self.sign == rhs_sign || self.is_digits_zero() {
+            // |self| += |rhs|
+            if self.digits.len() < rhs.digits.len() {
+                self.digits.resize(rhs.digits.len(), 0);
+            }
+            let mut carry: bool = false;
+            for (i, digit) in self.digits.iter_mut().enumerate() {
+                if i >= rhs.digits.len() && !carry {
+                    break;
                 }
-                Ordering::Less => {
-                    new_sign = b_sign;
-                    big = b;
-                    small = a;
+                let (sum, carry_1) = digit.overflowing_add(*rhs.digits.get(i).unwrap_or(&0));
+                let (sum, carry_2) = sum.overflowing_add(carry as Digit);
+                *digit = sum;
+                carry = carry_1 || carry_2;
+            }
+            if carry {
+                self.digits.push(1);
+            }
+            self.sign = rhs_sign;
+            self.validate();
+            return;
+        }
+        let mut borrow: bool = false;
+        match Self::digits_cmp(self, rhs) {
+            Ordering::Greater => {
+                // |self| -= |rhs|, the sign stays
+                for (i, digit) in self.digits.iter_mut().enumerate() {
+                    if i >= rhs.digits.len() && !borrow {
+                        break;
+                    }
+                    let (difference, borrow_1) =
+                        digit.overflowing_sub(*rhs.digits.get(i).unwrap_or(&0));
+                    let (difference, borrow_2) = difference.overflowing_sub(borrow as Digit);
+                    *digit = difference;
+                    borrow = borrow_1 || borrow_2;
                 }
-                Ordering::Equal => {
-                    return Self::zero();
+            }
+            Ordering::Less => {
+                // |self| = |rhs| - |self|, the sign flips to that of rhs
+                self.digits.resize(rhs.digits.len(), 0);
+                for (digit, &rhs_digit) in self.digits.iter_mut().zip(rhs.digits.iter()) {
+                    let (difference, borrow_1) = rhs_digit.overflowing_sub(*digit);
+                    let (difference, borrow_2) = difference.overflowing_sub(borrow as Digit);
+                    *digit = difference;
+                    borrow = borrow_1 || borrow_2;
                 }
+                self.sign = rhs_sign;
+            }
+            Ordering::Equal => {
+                self.digits.clear();
             }
-            let mut digits_result: Self = Self::digits_sub(big, small);
-            digits_result.sign = new_sign;
-            return digits_result;
         }
+        self.tidy();
+        self.validate();
+    }
+    pub fn add_assign(self: &mut Self, rhs: &Self) {
+        self.add_signed_assign(rhs, rhs.sign);
+    }
+    pub fn sub_assign(self: &mut Self, rhs: &Self) {
+        self.add_signed_assign(rhs, !rhs.sign);
+    }
+    pub fn add(a: Self, b: Self) -> Self {
+        let mut result: Self = a;
+        result.add_assign(&b);
+        result
     }
     pub fn sub(a: Self, b: Self) -> Self {
-        return Self::add(a, Self::opposite(b));
+        let mut result: Self = a;
+        result.sub_assign(&b);
+        result
     }
 
     pub fn high_shift(self: &mut Self, times: u32) {
-        let mut new_digits = vec![0; times as usize];
-        new_digits.append(&mut self.digits.clone());
-        self.digits = new_digits;
+        // *10^times
+        if times as usize <= DECIMAL_CHUNK_WIDTH * 4 {
+            // a few small multiplications in place beat building 10^times
+            let mut times: usize = times as usize;
+            while times > 0 {
+                let width: usize = min(times, DECIMAL_CHUNK_WIDTH);
+                self.digits_mul_add_small((DECIMAL_RADIX as Digit).pow(width as u32), 0);
+                times -= width;
+            }
+        } else {
+            let digits: Vec<Digit> = std::mem::take(&mut self.digits);
+            let shifted: Self = Self::digits_mul(
+                Self::from_digits(false, digits),
+                Self::pow10(times as usize),
+                0,
+            );
+            self.digits = shifted.digits;
+        }
+        self.tidy();
     }
     pub fn low_shift(self: &mut Self, times: u32) {
-        self.digits = self.digits.clone()[(times as usize)..].to_vec();
+        // drops the lowest `times` decimal digits
+        if times as usize <= DECIMAL_CHUNK_WIDTH * 4 {
+            let mut times: usize = times as usize;
+            while times > 0 {
+                let width: usize = min(times, DECIMAL_CHUNK_WIDTH);
+                self.digits_short_div_assign((DECIMAL_RADIX as Digit).pow(width as u32));
+                times -= width;
+            }
+        } else {
+            let digits: Vec<Digit> = std::mem::take(&mut self.digits);
+            let (shifted, _) = Self::digits_div_rem(
+                Self::from_digits(false, digits),
+                Self::pow10(times as usize),
+            );
+            self.digits = shifted.digits;
+        }
+        self.tidy();
+    }
+    fn digits_high_shift(self: &mut Self, times: usize) {
+        // *2^(32*times)
+        if !self.is_digits_zero() {
+            self.digits.splice(0..0, vec![0; times]);
+        }
     }
 
+    fn digits_mul_basecase(a: &[Digit], b: &[Digit], new_digits: &mut Vec<Digit>) {
+        new_digits.clear();
+        new_digits.resize(a.len() + b.len(), 0);
+        for (i, &a_digit) in a.iter().enumerate() {
+            if a_digit == 0 {
+                continue;
+            }
+            let mut carry: DoubleDigit = 0;
+            for (j, &b_digit) in b.iter().enumerate() {
+                let digit: DoubleDigit = a_digit as DoubleDigit * b_digit as DoubleDigit
+                    + new_digits[i + j] as DoubleDigit
+                    + carry;
+                new_digits[i + j] = digit as Digit;
+                carry = digit >> DIGIT_BITS;
+            }
+            new_digits[i + b.len()] = carry as Digit;
+        }
+    }
     fn digits_mul(a: Self, b: Self, level: u32) -> Self {
         // ref: PanSci 泛科學 - [地表最速乘法傳說！碰到大得要命的數字，這是最快的乘法方式](https://pansci.asia/archives/162365)
         let mut _max_digits_number: usize = a.digits.len();
         let mut _min_digits_number: usize = b.digits.len();
-        if _max_digits_number == 0
-            || _min_digits_number == 0
-            || (_max_digits_number == 1 && a.digits[0] == 0)
-            || (_min_digits_number == 1 && b.digits[0] == 0)
-        {
+        if a.is_digits_zero() || b.is_digits_zero() {
             return Self::zero();
         }
         if _min_digits_number > _max_digits_number {
-            let temp: usize = _min_digits_number;
-            _min_digits_number = _max_digits_number;
-            _max_digits_number = temp;
+            std::mem::swap(&mut _min_digits_number, &mut _max_digits_number);
+        }
+        if _min_digits_number <= KARATSUBA_THRESHOLD {
+            let mut new_digits: Vec<Digit> = Vec::new();
+            Self::digits_mul_basecase(&a.digits, &b.digits, &mut new_digits);
+            return Self::from_digits(false, new_digits);
         }
         let mut level: u32 = level;
         if level == 0 {
-            level = ((_max_digits_number as f64).log10() / 2_f64.log10()).ceil() as u32;
-            if level > 0 {
-                level -= 1;
+            // a fresh call, pick the algorithm by size
+            if _max_digits_number > _min_digits_number * 2 {
+                return Self::digits_mul_unbalanced(a, b);
             }
+            if _min_digits_number >= NTT_THRESHOLD && _max_digits_number <= NTT_MAX_DIGITS {
+                return Self::digits_mul_ntt(&a.digits, &b.digits);
+            }
+            if _min_digits_number >= TOOM_3_THRESHOLD {
+                return Self::digits_mul_toom_3(a, b);
+            }
+            level = ((_max_digits_number as f64).log10() / 2_f64.log10()).ceil() as u32;
+            level = level.saturating_sub(1);
         }
-        let group_width = 2_u32.pow(level as u32);
-        if level == 0 {
-            let a0: u8 = a.digits[0];
-            let a1: u8 = if a.digits.len() > 1 { a.digits[1] } else { 0 };
-            let b0: u8 = b.digits[0];
-            let b1: u8 = if b.digits.len() > 1 { b.digits[1] } else { 0 };
-
-            let r1: u8 = a0 * b0;
-            let r4: u8 = a1 * b1;
-            let r23: u16 = ((a0 + a1) as u16) * ((b0 + b1) as u16) - r1 as u16 - r4 as u16;
-
-            let radix: u8 = BASIC_INT_RADIX as u8;
-            let mut messy_result: Self = Self::new(
-                false,
-                vec![
-                    r1 % radix,
-                    r1 / radix + (r23 % (radix as u16)) as u8,
-                    (r23 / (radix as u16)) as u8 + r4 % radix,
-                    r4 / radix,
-                ],
-            );
-            messy_result.tidy();
-            return messy_result;
-        }
+        let group_width: usize = 2_usize.pow(level);
         // n11 n12
         // n21 n22
-        let a_group_width: usize = min(group_width as usize, a.digits.len());
-        let b_group_width: usize = min(group_width as usize, b.digits.len());
-        let n12: Self = Self::new(false, a.digits[..a_group_width].to_vec());
-        let n22: Self = Self::new(false, b.digits[..b_group_width].to_vec());
-        let n11: Self = Self::new(false, a.digits[a_group_width..].to_vec());
-        let n21: Self = Self::new(false, b.digits[b_group_width..].to_vec());
+        let a_group_width: usize = min(group_width, a.digits.len());
+        let b_group_width: usize = min(group_width, b.digits.len());
+        let n12: Self = Self::from_digits(false, a.digits[..a_group_width].to_vec());
+        let n22: Self = Self::from_digits(false, b.digits[..b_group_width].to_vec());
+        let n11: Self = Self::from_digits(false, a.digits[a_group_width..].to_vec());
+        let n21: Self = Self::from_digits(false, b.digits[b_group_width..].to_vec());
         let r1 = Self::digits_mul(n12.clone(), n22.clone(), level - 1);
         let mut r4 = Self::digits_mul(n11.clone(), n21.clone(), level - 1);
         let mut r23 = Self::digits_sub(
@@ -315,274 +1128,5753 @@ impl Integer {
             ),
             Self::digits_add(r1.clone(), r4.clone()),
         );
-        r23.high_shift(group_width);
-        r4.high_shift(group_width * 2);
+        r23.digits_high_shift(group_width);
+        r4.digits_high_shift(group_width * 2);
         Self::digits_add(Self::digits_add(r1, r23), r4)
     }
+    fn digits_mul_unbalanced(a: Self, b: Self) -> Self {
+        // cut the longer number into pieces as long as the shorter one
+        let (long, short): (Self, Self) = if a.digits.len() >= b.digits.len() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let width: usize = short.digits.len();
+        let mut result: Self = Self::zero();
+        for chunk in long.digits.chunks(width).rev() {
+            result.digits_high_shift(width);
+            let part: Self = Self::from_digits(false, chunk.to_vec());
+            result = Self::digits_add(result, Self::digits_mul(part, short.clone(), 0));
+        }
+        result
+    }
+    fn digits_split(self: &Self, width: usize, index: usize) -> Self {
+        // the index-th group of width digits, as a magnitude
+        let start: usize = min(width * index, self.digits.len());
+        let end: usize = min(width * (index + 1), self.digits.len());
+        Self::from_digits(false, self.digits[start..end].to_vec())
+    }
+    fn digits_mul_toom_3(a: Self, b: Self) -> Self {
+        // ref: Bodrato, "Towards Optimal Toom-Cook Multiplication for Univariate and Multivariate Polynomials in Characteristic 2 and 0"
+        // evaluate at 0, 1, -1, -2 and infinity, then interpolate
+        let width: usize = max(a.digits.len(), b.digits.len()).div_ceil(3);
+        let (a0, a1, a2): (Self, Self, Self) = (
+            a.digits_split(width, 0),
+            a.digits_split(width, 1),
+            a.digits_split(width, 2),
+        );
+        let (b0, b1, b2): (Self, Self, Self) = (
+            b.digits_split(width, 0),
+            b.digits_split(width, 1),
+            b.digits_split(width, 2),
+        );
+        let evaluate = |x0: &Self, x1: &Self, x2: &Self| -> (Self, Self, Self) {
+            let sum: Self = Self::add(x0.clone(), x2.clone());
+            let at_one: Self = Self::add(sum.clone(), x1.clone());
+            let at_minus_one: Self = Self::sub(sum, x1.clone());
+            let at_minus_two: Self = Self::sub(
+                Self::shl(Self::add(at_minus_one.clone(), x2.clone()), 1),
+                x0.clone(),
+            );
+            (at_one, at_minus_one, at_minus_two)
+        };
+        let (a_1, a_m1, a_m2): (Self, Self, Self) = evaluate(&a0, &a1, &a2);
+        let (b_1, b_m1, b_m2): (Self, Self, Self) = evaluate(&b0, &b1, &b2);
+        let r0: Self = Self::mul(a0, b0);
+        let r_1: Self = Self::mul(a_1, b_1);
+        let r_m1: Self = Self::mul(a_m1, b_m1);
+        let r_m2: Self = Self::mul(a_m2, b_m2);
+        let r4: Self = Self::mul(a2, b2);
+        // every division below is exact
+        let mut r3: Self = Self::div_exact(Self::sub(r_m2, r_1.clone()), Self::from(3)).unwrap();
+        let mut r1: Self = Self::shr(Self::sub(r_1, r_m1.clone()), 1);
+        let mut r2: Self = Self::sub(r_m1, r0.clone());
+        r3 = Self::add(
+            Self::shr(Self::sub(r2.clone(), r3), 1),
+            Self::shl(r4.clone(), 1),
+        );
+        r2 = Self::sub(Self::add(r2, r1.clone()), r4.clone());
+        r1 = Self::sub(r1, r3.clone());
+        // r0 + r1 x + r2 x^2 + r3 x^3 + r4 x^4, where x = 2^(32*width)
+        let mut result: Self = r4;
+        for coefficient in [r3, r2, r1, r0] {
+            result.digits_high_shift(width);
+            result = Self::add(result, coefficient);
+        }
+        result
+    }
+    fn digits_mul_ntt(a: &[Digit], b: &[Digit]) -> Self {
+        // convolve the digits modulo three NTT primes, then rebuild each
+        // coefficient (< len * 2^64 < p1 * p2 * p3) with the chinese remainder theorem
+        let length: usize = (a.len() + b.len()).next_power_of_two();
+        let [residues_1, residues_2, residues_3]: [Vec<u64>; 3] = NTT_PRIMES.map(|modulus| {
+            let mut fa: Vec<u64> = a.iter().map(|&digit| digit as u64 % modulus).collect();
+            let mut fb: Vec<u64> = b.iter().map(|&digit| digit as u64 % modulus).collect();
+            fa.resize(length, 0);
+            fb.resize(length, 0);
+            ntt(&mut fa, modulus, false);
+            ntt(&mut fb, modulus, false);
+            for (x, y) in fa.iter_mut().zip(fb.iter()) {
+                *x = *x * y % modulus;
+            }
+            ntt(&mut fa, modulus, true);
+            fa
+        });
+        let [p1, p2, p3]: [u64; 3] = NTT_PRIMES;
+        let p1_inverse: u64 = pow_mod_u64(p1 % p2, p2 - 2, p2);
+        let p12_inverse: u64 = pow_mod_u64(p1 * p2 % p3, p3 - 2, p3);
+        let mut new_digits: Vec<Digit> = Vec::with_capacity(length + 4);
+        let mut carry: u128 = 0;
+        let residues = residues_1.iter().zip(&residues_2).zip(&residues_3);
+        for ((&r1, &r2), &r3) in residues.take(a.len() + b.len()) {
+            // garner's algorithm
+            let t1: u64 = (r2 + p2 - r1 % p2) % p2 * p1_inverse % p2;
+            let x12: u64 = r1 + p1 * t1;
+            let t2: u64 = (r3 + p3 - x12 % p3) % p3 * p12_inverse % p3;
+            let value: u128 = x12 as u128 + (p1 * p2) as u128 * t2 as u128;
+            carry += value;
+            new_digits.push(carry as Digit);
+            carry >>= DIGIT_BITS;
+        }
+        while carry > 0 {
+            new_digits.push(carry as Digit);
+            carry >>= DIGIT_BITS;
+        }
+        Self::from_digits(false, new_digits)
+    }
     pub fn mul(a: Self, b: Self) -> Self {
         let new_sign: bool = a.sign != b.sign; // ++ => +, -- => +, +- => -, -+ => -
         let mut digits_result: Self = Self::digits_mul(a, b, 0);
-        digits_result.sign = new_sign;
+        digits_result.sign = new_sign && !digits_result.is_digits_zero();
+        digits_result.validate();
         return digits_result;
     }
-}
-
-/* Float */
-
-#[derive(Clone)]
-pub struct Float {
-    point: Integer,
-    value: Integer,
-}
+    pub fn mul_into(a: &Self, b: &Self, out: &mut Self) {
+        // out = a * b, small products are written straight into the digits of out
+        if min(a.digits.len(), b.digits.len()) <= KARATSUBA_THRESHOLD {
+            Self::digits_mul_basecase(&a.digits, &b.digits, &mut out.digits);
+        } else {
+            out.digits = Self::digits_mul(a.clone(), b.clone(), 0).digits;
+        }
+        out.sign = a.sign != b.sign;
+        out.tidy();
+        out.validate();
+    }
 
-#[allow(dead_code)]
-impl Float {
-    pub fn new(point: Integer, value: Integer) -> Self {
-        Self {
-            point: point,
-            value: value,
+    fn is_digits_zero(self: &Self) -> bool {
+        self.digits.iter().all(|&digit| digit == 0)
+    }
+    fn digits_short_div(a: Self, divisor: Digit) -> (Self, Digit) {
+        // divisor != 0
+        let mut remainder: DoubleDigit = 0;
+        let mut new_digits: Vec<Digit> = vec![0; a.digits.len()];
+        for i in (0..a.digits.len()).rev() {
+            let current: DoubleDigit = (remainder << DIGIT_BITS) | a.digits[i] as DoubleDigit;
+            new_digits[i] = (current / divisor as DoubleDigit) as Digit;
+            remainder = current % divisor as DoubleDigit;
         }
+        (Self::from_digits(false, new_digits), remainder as Digit)
     }
-    pub fn zero() -> Self {
-        Self::new(Integer::zero(), Integer::zero())
+    fn digits_short_div_assign(self: &mut Self, divisor: Digit) -> Digit {
+        // |self| /= divisor in place, returns the remainder, divisor != 0
+        let mut remainder: DoubleDigit = 0;
+        for digit in self.digits.iter_mut().rev() {
+            let current: DoubleDigit = (remainder << DIGIT_BITS) | *digit as DoubleDigit;
+            *digit = (current / divisor as DoubleDigit) as Digit;
+            remainder = current % divisor as DoubleDigit;
+        }
+        self.tidy();
+        remainder as Digit
     }
+    fn digits_div_rem(a: Self, b: Self) -> (Self, Self) {
+        // ref: Knuth, TAOCP Vol. 2, 4.3.1, Algorithm D
+        // a, b: magnitudes only, b != 0
+        let a: Self = Self::from_digits(false, a.digits);
+        let b: Self = Self::from_digits(false, b.digits);
+        if Self::digits_cmp(&a, &b) == Ordering::Less {
+            return (Self::zero(), a);
+        }
+        if b.digits.len() == 1 {
+            let (quotient, remainder) = Self::digits_short_div(a, b.digits[0]);
+            return (quotient, Self::from_digits(false, vec![remainder]));
+        }
+        if b.digits.len() >= DIVISION_THRESHOLD
+            && a.digits.len() - b.digits.len() >= DIVISION_THRESHOLD
+        {
+            return Self::digits_div_rem_recursive(a, b);
+        }
 
-    pub fn from_f64(number: f64) -> Self {
-        let number_string_list: Vec<String> = number
-            .abs()
-            .to_string()
-            .split('.')
-            .map(|s| (&s).to_string())
-            .collect::<Vec<String>>();
-        let integer: usize = number_string_list[0].parse::<usize>().unwrap() as usize;
-        let float: f64 = if number_string_list.len() > 1 {
-            number_string_list[1].parse::<usize>().unwrap()
-        } else {
-            0
-        } as f64
-            / 10.0;
-
-        let radix: usize = BASIC_INT_RADIX as usize;
-        let mut integer: usize = integer;
-        let mut digits_integer: Vec<u8> = Vec::new();
-        while integer > radix {
-            let digit: usize = integer % radix;
-            integer -= digit;
-            integer /= radix;
-            digits_integer.push(digit as u8);
-        }
-        digits_integer.push(integer as u8);
-
-        let radix: f64 = BASIC_INT_RADIX as f64;
-        let mut float: f64 = float;
-        let mut digits_float: Vec<u8> = Vec::new();
-        let mut digit_value_now: f64 = 0.0;
-        while float >= digit_value_now {
-            if digit_value_now != 0.0 {
-                // println!("{}, {}", float, digit_value_now);
-                let digit: f64 = (float / digit_value_now).floor();
-                float -= digit * digit_value_now;
-                digits_float.push(digit as u8);
-            }
-            digit_value_now = 1.0 / (radix.powf(digits_float.len() as f64 + 1.0));
-        }
-        digits_float.push(float as u8);
-        digits_float.reverse();
+        // normalize, so that the top bit of the divisor is set
+        let n: usize = b.digits.len();
+        let m: usize = a.digits.len() - n;
+        let shift: u32 = b.digits[n - 1].leading_zeros();
+        let shift_digits = |digits: &Vec<Digit>| -> Vec<Digit> {
+            let mut shifted: Vec<Digit> = Vec::with_capacity(digits.len() + 1);
+            let mut carry: Digit = 0;
+            for &digit in digits {
+                shifted.push((digit << shift) | carry);
+                carry = if shift == 0 {
+                    0
+                } else {
+                    digit >> (DIGIT_BITS - shift)
+                };
+            }
+            shifted.push(carry);
+            shifted
+        };
+        let mut u: Vec<Digit> = shift_digits(&a.digits);
+        let mut v: Vec<Digit> = shift_digits(&b.digits);
+        v.pop();
 
-        Self::new(
-            Integer::from_isize(digits_float.len() as isize),
-            Integer::new(number < 0.0, vec![digits_float, digits_integer].concat()),
-        )
+        let mut quotient_digits: Vec<Digit> = vec![0; m + 1];
+        for j in (0..=m).rev() {
+            let top: DoubleDigit =
+                ((u[j + n] as DoubleDigit) << DIGIT_BITS) | u[j + n - 1] as DoubleDigit;
+            let mut q_hat: DoubleDigit = top / v[n - 1] as DoubleDigit;
+            let mut r_hat: DoubleDigit = top % v[n - 1] as DoubleDigit;
+            while q_hat >= LIMB_RADIX
+                || q_hat * v[n - 2] as DoubleDigit
+                    > ((r_hat << DIGIT_BITS) | u[j + n - 2] as DoubleDigit)
+            {
+                q_hat -= 1;
+                r_hat += v[n - 1] as DoubleDigit;
+                if r_hat >= LIMB_RADIX {
+                    break;
+                }
+            }
+            // u[j..=j + n] -= q_hat * v
+            let mut borrow: bool = false;
+            let mut carry: DoubleDigit = 0;
+            for i in 0..=n {
+                let product: DoubleDigit = if i < n {
+                    q_hat * v[i] as DoubleDigit + carry
+                } else {
+                    carry
+                };
+                carry = product >> DIGIT_BITS;
+                let (digit, borrow_1) = u[i + j].overflowing_sub(product as Digit);
+                let (digit, borrow_2) = digit.overflowing_sub(borrow as Digit);
+                borrow = borrow_1 || borrow_2;
+                u[i + j] = digit;
+            }
+            if borrow {
+                // q_hat was one too large, add v back
+                q_hat -= 1;
+                let mut carry: DoubleDigit = 0;
+                for i in 0..=n {
+                    let digit: DoubleDigit = u[i + j] as DoubleDigit
+                        + (if i < n { v[i] } else { 0 }) as DoubleDigit
+                        + carry;
+                    u[i + j] = digit as Digit;
+                    carry = digit >> DIGIT_BITS;
+                }
+            }
+            quotient_digits[j] = q_hat as Digit;
+        }
+
+        let quotient: Self = Self::from_digits(false, quotient_digits);
+        let remainder_digits: Vec<Digit> = (0..n)
+            .map(|i| {
+                if shift == 0 {
+                    u[i]
+                } else {
+                    (u[i] >> shift) | (u[i + 1] << (DIGIT_BITS - shift))
+                }
+            })
+            .collect();
+        (quotient, Self::from_digits(false, remainder_digits))
     }
-    pub fn from_string(string: String) -> Self {
-        let mut sign: bool = false;
-        let mut chars: Vec<char> = string.chars().collect();
-        if chars[0] == '-' {
-            chars = chars[1..].to_vec();
-            sign = true;
+    fn digits_div_rem_recursive(a: Self, b: Self) -> (Self, Self) {
+        // ref: Brent & Zimmermann, Modern Computer Arithmetic, 1.4.3, Algorithm 1.8 and 1.9
+        // a, b: magnitudes only, a >= b
+        // normalize, so that the top bit of the divisor is set
+        let shift: usize = b.digits[b.digits.len() - 1].leading_zeros() as usize;
+        let a: Self = a.digits_shl_bits(shift);
+        let b: Self = b.digits_shl_bits(shift);
+        let n: usize = b.digits.len();
+        let mut m: usize = a.digits.len() - n;
+        let mut rest: Self = a;
+        let mut quotient: Self = Self::zero();
+        // a long dividend is divided n digits at a time, like a schoolbook division in base 2^(32*n)
+        while m > n {
+            let low: Self = rest.digits_split(m - n, 0);
+            let high: Self = rest.digits_shr_bits((m - n) * DIGIT_BITS as usize);
+            let (q, mut r) = Self::digits_div_rem_balanced(high, &b);
+            quotient.digits_high_shift(n);
+            quotient = Self::digits_add(quotient, q);
+            r.digits_high_shift(m - n);
+            rest = Self::digits_add(r, low);
+            m -= n;
         }
-        let mut dot_pos: usize = match chars.iter().position(|&c| c == '.') {
-            Some(p) => p,
-            None => chars.len(),
-        };
-        if dot_pos < chars.len() && chars[dot_pos] == '.' {
-            chars.remove(dot_pos);
+        let (q, remainder) = Self::digits_div_rem_balanced(rest, &b);
+        quotient.digits_high_shift(m);
+        quotient = Self::digits_add(quotient, q);
+        (quotient, remainder.digits_shr_bits(shift))
+    }
+    fn digits_div_rem_balanced(a: Self, b: &Self) -> (Self, Self) {
+        // b normalized, a at most twice as long as b
+        let n: usize = b.digits.len();
+        let m: usize = a.digits.len().saturating_sub(n);
+        if m < DIVISION_THRESHOLD {
+            return Self::digits_div_rem(a, b.clone());
         }
-        dot_pos = chars.len() - dot_pos;
-        let mut digits: Vec<u8> = chars
-            .iter()
-            .map(|c| c.to_digit(BASIC_INT_RADIX as u32).unwrap() as u8)
-            .collect();
-        digits.reverse();
-        Self::new(
-            Integer::from_isize(dot_pos as isize),
-            Integer::new(sign, digits),
-        )
+        if n > m + 1 {
+            // only the top m + 1 digits of b matter for the quotient, which is then at most 2 too large
+            let bits: usize = (n - m - 1) * DIGIT_BITS as usize;
+            let (mut quotient, _) =
+                Self::digits_div_rem_balanced(a.digits_shr_bits(bits), &b.digits_shr_bits(bits));
+            let mut remainder: Self = Self::sub(a, Self::mul(quotient.clone(), b.clone()));
+            while remainder.sign {
+                quotient = Self::sub(quotient, Self::one());
+                remainder = Self::add(remainder, b.clone());
+            }
+            return (quotient, remainder);
+        }
+        // split b = b_high * 2^(32*k) + b_low, and find the high then the low half of the quotient
+        let k: usize = m / 2;
+        let b_high: Self = b.digits_shr_bits(k * DIGIT_BITS as usize);
+        let b_low: Self = b.digits_split(k, 0);
+        let (mut q_high, r_high) =
+            Self::digits_div_rem_balanced(a.digits_shr_bits(2 * k * DIGIT_BITS as usize), &b_high);
+        let mut rest: Self = r_high;
+        rest.digits_high_shift(2 * k);
+        rest = Self::add(rest, a.digits_split(2 * k, 0));
+        let mut correction: Self = Self::mul(q_high.clone(), b_low.clone());
+        correction.digits_high_shift(k);
+        rest = Self::sub(rest, correction);
+        // q_high may be a few too large, the loops below run at most twice
+        let mut b_shifted: Self = b.clone();
+        b_shifted.digits_high_shift(k);
+        while rest.sign {
+            q_high = Self::sub(q_high, Self::one());
+            rest = Self::add(rest, b_shifted.clone());
+        }
+        let (mut q_low, r_low) =
+            Self::digits_div_rem_balanced(rest.digits_shr_bits(k * DIGIT_BITS as usize), &b_high);
+        let mut remainder: Self = r_low;
+        remainder.digits_high_shift(k);
+        remainder = Self::add(remainder, rest.digits_split(k, 0));
+        remainder = Self::sub(remainder, Self::mul(q_low.clone(), b_low));
+        while remainder.sign {
+            q_low = Self::sub(q_low, Self::one());
+            remainder = Self::add(remainder, b.clone());
+        }
+        q_high.digits_high_shift(k);
+        (Self::digits_add(q_high, q_low), remainder)
     }
-    pub fn from_integer(n: Integer) -> Self {
-        Self::new(Integer::zero(), n)
+    pub fn div_rem(a: Self, b: Self) -> Result<(Self, Self), ArithmeticError> {
+        // truncated division: quotient rounds toward zero, remainder takes the sign of a
+        if b.is_digits_zero() {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        let a_sign: bool = a.sign;
+        let b_sign: bool = b.sign;
+        let (mut quotient, mut remainder) = Self::digits_div_rem(a, b);
+        quotient.sign = a_sign != b_sign && !quotient.is_digits_zero();
+        remainder.sign = a_sign && !remainder.is_digits_zero();
+        quotient.validate();
+        remainder.validate();
+        return Ok((quotient, remainder));
     }
-    pub fn to_f64(self: Self) -> f64 {
-        // println!("{:?}", self.value.digits);
-        let digits: Vec<u8> = self.value.digits.clone();
-        digits
-            .iter()
-            .enumerate()
-            .map(|(i, &n)| {
-                (n as f64)
-                    * (BASIC_INT_RADIX as f64)
-                        .powf(i as f64 - Integer::to_isize(self.point.clone()) as f64)
-            })
-            .sum::<f64>()
-            * (if self.value.sign { -1.0 } else { 1.0 })
-    }
-    pub fn to_string(self: Self) -> String {
-        let mut digits_vec_char: Vec<char> = self
-            .value
-            .digits
-            .clone()
-            .iter()
-            .map(|&n| from_digit(n as u32, 16).unwrap())
-            .collect::<Vec<char>>();
-        let mut digits_string: String;
-        let point: isize = Integer::to_isize(self.point.clone());
-        let digits_len: isize = digits_vec_char.len() as isize;
-        let dot_pos: usize;
-        if point > digits_len {
-            digits_vec_char = vec![
-                digits_vec_char,
-                vec!['0'; max(point - digits_len, 0) as usize],
-            ]
-            .concat();
-            dot_pos = digits_vec_char.len();
-        } else if point < 0 {
-            digits_vec_char =
-                vec![vec!['0'; min(point, 0).abs() as usize], digits_vec_char].concat();
-            dot_pos = 0;
+    pub fn div(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        Self::div_rem(a, b).map(|(quotient, _)| quotient)
+    }
+    pub fn rem(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        Self::div_rem(a, b).map(|(_, remainder)| remainder)
+    }
+    pub fn div_mod_floor(a: Self, b: Self) -> Result<(Self, Self), ArithmeticError> {
+        // floored division: quotient rounds toward -inf, remainder takes the sign of b
+        let b_sign: bool = b.sign;
+        let (quotient, remainder) = Self::div_rem(a, b.clone())?;
+        if !remainder.is_digits_zero() && remainder.sign != b_sign {
+            return Ok((Self::sub(quotient, Self::one()), Self::add(remainder, b)));
+        }
+        return Ok((quotient, remainder));
+    }
+    pub fn div_floor(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        Self::div_mod_floor(a, b).map(|(quotient, _)| quotient)
+    }
+    pub fn mod_floor(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        Self::div_mod_floor(a, b).map(|(_, remainder)| remainder)
+    }
+    pub fn div_rem_euclid(a: Self, b: Self) -> Result<(Self, Self), ArithmeticError> {
+        // Euclidean division: remainder is always in [0, |b|)
+        let b_sign: bool = b.sign;
+        let (quotient, remainder) = Self::div_rem(a, b.clone())?;
+        if remainder.sign {
+            if b_sign {
+                return Ok((Self::add(quotient, Self::one()), Self::sub(remainder, b)));
+            } else {
+                return Ok((Self::sub(quotient, Self::one()), Self::add(remainder, b)));
+            }
+        }
+        return Ok((quotient, remainder));
+    }
+    pub fn div_euclid(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        Self::div_rem_euclid(a, b).map(|(quotient, _)| quotient)
+    }
+    pub fn rem_euclid(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        Self::div_rem_euclid(a, b).map(|(_, remainder)| remainder)
+    }
+    pub fn div_exact(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        // fast path for when b is known to divide a, the remainder is never built
+        if b.is_digits_zero() {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        let new_sign: bool = a.sign != b.sign;
+        let mut quotient: Self;
+        if b.digits.len() == 1 {
+            let remainder: Digit;
+            (quotient, remainder) = Self::digits_short_div(a, b.digits[0]);
+            debug_assert!(remainder == 0, "Integer::div_exact: b does not divide a");
         } else {
-            dot_pos = point as usize;
+            let remainder: Self;
+            (quotient, remainder) = Self::digits_div_rem(a, b);
+            debug_assert!(
+                remainder.is_digits_zero(),
+                "Integer::div_exact: b does not divide a"
+            );
         }
-        digits_vec_char.reverse();
-        digits_string = digits_vec_char.iter().collect();
-        digits_string.insert(digits_vec_char.len() - dot_pos, '.');
-        (if self.value.sign { "-" } else { "" }).to_string() + digits_string.as_str()
+        quotient.sign = new_sign && !quotient.is_digits_zero();
+        quotient.validate();
+        return Ok(quotient);
     }
 
-    pub fn opposite(n: Self) -> Self {
-        Self::new(n.point, Integer::opposite(n.value))
+    fn digits_bits(self: &Self) -> Vec<bool> {
+        // [top bit, ..., lowest bit], magnitude only
+        let mut bits: Vec<bool> = Vec::new();
+        for &digit in self.digits.iter().rev() {
+            for i in (0..DIGIT_BITS).rev() {
+                bits.push((digit >> i) & 1 == 1);
+            }
+        }
+        let top: usize = bits.iter().position(|&bit| bit).unwrap_or(bits.len());
+        bits[top..].to_vec()
     }
-
-    fn digits_cmp(a: Self, b: Self) -> Ordering {
-        let a_len: Integer =
-            Integer::sub(Integer::from_isize(a.value.digits.len() as isize), a.point);
-        let b_len: Integer =
-            Integer::sub(Integer::from_isize(b.value.digits.len() as isize), b.point);
-        match Integer::cmp(a_len, b_len) {
-            Ordering::Greater => Ordering::Greater,
-            Ordering::Less => Ordering::Less,
-            Ordering::Equal => Integer::digits_cmp(a.value, b.value),
+    pub fn pow_u32(base: Self, exp: u32) -> Self {
+        Self::pow(base, Self::from_digits(false, vec![exp])).unwrap()
+    }
+    pub fn pow(base: Self, exp: Self) -> Result<Self, ArithmeticError> {
+        // left-to-right binary exponentiation, 0^0 = 1
+        if exp.sign {
+            let magnitude_is_one: bool = base.digits == [1];
+            if base.is_digits_zero() {
+                return Err(ArithmeticError::DivisionByZero);
+            } else if !magnitude_is_one {
+                return Err(ArithmeticError::NegativeExponent);
+            }
         }
+        let new_sign: bool = base.sign && exp.digits[0] & 1 == 1;
+        let base: Self = Self::from_digits(false, base.digits);
+        let mut result: Self = Self::one();
+        for bit in exp.digits_bits() {
+            result = Self::digits_mul(result.clone(), result, 0);
+            if bit {
+                result = Self::digits_mul(result, base.clone(), 0);
+            }
+        }
+        result.sign = new_sign && !result.is_digits_zero();
+        result.validate();
+        return Ok(result);
     }
-    pub fn cmp(a: Self, b: Self) -> Ordering {
-        if !a.value.sign && b.value.sign {
-            // a: (+), b: (-)
-            return Ordering::Greater;
-        } else if a.value.sign && !b.value.sign {
-            // a: (-), b: (+)
-            return Ordering::Less;
-        } else if !a.value.sign {
-            // a: (+), b: (+)
-            return Self::digits_cmp(a, b);
-        } else {
-            // a: (-), b: (-)
-            return match Self::digits_cmp(a, b) {
-                Ordering::Greater => Ordering::Less,
-                Ordering::Less => Ordering::Greater,
-                Ordering::Equal => Ordering::Equal,
-            };
+    pub fn pow_mod(base: Self, exp: Self, modulus: Self) -> Result<Self, ArithmeticError> {
+        // base^exp mod |modulus|, the result is always in [0, |modulus|)
+        // a negative exp needs base to be invertible
+        if modulus.is_digits_zero() {
+            return Err(ArithmeticError::DivisionByZero);
         }
+        let modulus: Self = Self::from_digits(false, modulus.digits);
+        let mut base: Self = Self::rem_euclid(base, modulus.clone())?;
+        if exp.sign {
+            // base^-exp = (base^-1)^exp
+            base = Self::mod_inverse(base, modulus.clone())?;
+        }
+        let (_, mut result) = Self::digits_div_rem(Self::one(), modulus.clone());
+        for bit in exp.digits_bits() {
+            (_, result) =
+                Self::digits_div_rem(Self::digits_mul(result.clone(), result, 0), modulus.clone());
+            if bit {
+                (_, result) = Self::digits_div_rem(
+                    Self::digits_mul(result, base.clone(), 0),
+                    modulus.clone(),
+                );
+            }
+        }
+        result.validate();
+        return Ok(result);
     }
 
-    fn tidy(self: &mut Self) {
-        let digits_len: usize = self.value.digits.len();
-        let mut zero_count: usize = 0;
-        for i in 0..digits_len {
-            if self.value.digits[i] == 0 {
-                zero_count += 1;
+    fn odd_product(low: u64, high: u64) -> Self {
+        // the odd numbers in (low, high], multiplied by binary splitting
+        let first: u64 = (low + 1) | 1;
+        if first > high {
+            return Self::one();
+        }
+        let count: u64 = (high - first) / 2 + 1;
+        if count <= 2 {
+            return Self::from(first as u128 * if count == 2 { first as u128 + 2 } else { 1 });
+        }
+        let middle: u64 = first - 1 + 2 * (count / 2);
+        Self::mul(
+            Self::odd_product(low, middle),
+            Self::odd_product(middle, high),
+        )
+    }
+    fn range_product(low: &Self, count: u64) -> Self {
+        // (low + 1) * (low + 2) * ... * (low + count), multiplied by binary splitting
+        match count {
+            0 => Self::one(),
+            1 => Self::add(low.clone(), Self::one()),
+            _ => {
+                let half: u64 = count / 2;
+                let middle: Self = Self::add(low.clone(), Self::from(half));
+                Self::mul(
+                    Self::range_product(low, half),
+                    Self::range_product(&middle, count - half),
+                )
+            }
+        }
+    }
+    pub fn factorial(n: u32) -> Self {
+        // ref: Luschny, "Fast Factorial Functions", binary split
+        // n! = 2^(n - popcount(n)) * the product over i of (odd numbers <= n / 2^i)
+        let n: u64 = n as u64;
+        let mut odd_part: Self = Self::one();
+        let mut running: Self = Self::one();
+        for i in (0..u64::BITS - n.leading_zeros()).rev() {
+            running = Self::mul(running, Self::odd_product(n >> (i + 1), n >> i));
+            odd_part = Self::mul(odd_part, running.clone());
+        }
+        let result: Self = Self::shl(odd_part, (n - n.count_ones() as u64) as usize);
+        result.validate();
+        result
+    }
+    pub fn binomial(n: Self, k: u32) -> Self {
+        // n choose k, also for a negative n: (-n choose k) = (-1)^k * (n + k - 1 choose k)
+        if n.sign {
+            let mut result: Self =
+                Self::binomial(Self::sub(Self::from(k), Self::add(n, Self::one())), k);
+            result.sign = k % 2 == 1 && !result.is_digits_zero();
+            result.validate();
+            return result;
+        }
+        if Self::cmp(Self::from(k), n.clone()) == Ordering::Greater {
+            return Self::zero();
+        }
+        // (n choose k) = (n choose n - k)
+        let k: u32 = match u32::try_from(&Self::sub(n.clone(), Self::from(k))) {
+            Ok(other) if other < k => other,
+            _ => k,
+        };
+        let low: Self = Self::sub(n, Self::from(k));
+        let result: Self =
+            Self::div_exact(Self::range_product(&low, k as u64), Self::factorial(k)).unwrap();
+        result.validate();
+        result
+    }
+    pub fn multinomial(ks: &[u32]) -> Self {
+        // (k1 + k2 + ... )! / (k1! * k2! * ...), as a product of binomials
+        let mut result: Self = Self::one();
+        let mut total: Self = Self::zero();
+        for &k in ks {
+            total = Self::add(total, Self::from(k));
+            result = Self::mul(result, Self::binomial(total.clone(), k));
+        }
+        result
+    }
+    fn fibonacci_pair(n: u32) -> (Self, Self) {
+        // (F(n), F(n + 1)) by fast doubling
+        // F(2k) = F(k) * (2F(k + 1) - F(k)), F(2k + 1) = F(k)^2 + F(k + 1)^2
+        let mut f: Self = Self::zero();
+        let mut f_next: Self = Self::one();
+        for i in (0..u32::BITS - n.leading_zeros()).rev() {
+            let f_double: Self = Self::mul(
+                f.clone(),
+                Self::sub(Self::shl(f_next.clone(), 1), f.clone()),
+            );
+            let f_double_next: Self =
+                Self::add(Self::mul(f.clone(), f), Self::mul(f_next.clone(), f_next));
+            if (n >> i) & 1 == 1 {
+                f_next = Self::add(f_double.clone(), f_double_next.clone());
+                f = f_double_next;
             } else {
+                f = f_double;
+                f_next = f_double_next;
+            }
+        }
+        (f, f_next)
+    }
+    pub fn fibonacci(n: u32) -> Self {
+        Self::fibonacci_pair(n).0
+    }
+    pub fn lucas(n: u32) -> Self {
+        // L(n) = 2F(n + 1) - F(n)
+        let (f, f_next) = Self::fibonacci_pair(n);
+        Self::sub(Self::shl(f_next, 1), f)
+    }
+
+    fn digits_shr_bits(self: &Self, bits: usize) -> Self {
+        // magnitude only, / 2^bits
+        let digit_shift: usize = bits / DIGIT_BITS as usize;
+        let bit_shift: u32 = (bits % DIGIT_BITS as usize) as u32;
+        if digit_shift >= self.digits.len() {
+            return Self::zero();
+        }
+        let high: &[Digit] = &self.digits[digit_shift..];
+        let new_digits: Vec<Digit> = (0..high.len())
+            .map(|i| {
+                if bit_shift == 0 {
+                    high[i]
+                } else {
+                    let next: Digit = if i + 1 < high.len() { high[i + 1] } else { 0 };
+                    (high[i] >> bit_shift) | (next << (DIGIT_BITS - bit_shift))
+                }
+            })
+            .collect();
+        Self::from_digits(false, new_digits)
+    }
+    fn digits_mul_small(self: &Self, factor: Digit) -> Self {
+        let mut result: Self = Self::from_digits(false, self.digits.clone());
+        result.digits_mul_add_small(factor, 0);
+        result.tidy();
+        result
+    }
+    fn lehmer_cofactors(a: &Self, b: &Self) -> (i64, i64, i64, i64) {
+        // the euclidean steps on (a, b) simulated on the leading digit of a, a >= b and a has more
+        // than one digit, (a, b) becomes (a_co * a + b_co * b, c_co * a + d_co * b)
+        // b_co == 0 means no step could be simulated and a full division is needed
+        let shift: usize = a.bit_length() - DIGIT_BITS as usize;
+        let mut x_hat: i64 = a.digits_shr_bits(shift).digits[0] as i64;
+        let mut y_hat: i64 = b.digits_shr_bits(shift).digits[0] as i64;
+        let (mut a_co, mut b_co, mut c_co, mut d_co): (i64, i64, i64, i64) = (1, 0, 0, 1);
+        while y_hat + c_co != 0 && y_hat + d_co != 0 {
+            let q: i64 = (x_hat + a_co) / (y_hat + c_co);
+            if q != (x_hat + b_co) / (y_hat + d_co) {
                 break;
             }
+            (a_co, c_co) = (c_co, a_co - q * c_co);
+            (b_co, d_co) = (d_co, b_co - q * d_co);
+            (x_hat, y_hat) = (y_hat, x_hat - q * y_hat);
         }
-        if zero_count > 0 {
-            self.value.low_shift(zero_count as u32);
-            self.point = Integer::sub(self.point.clone(), Integer::from_isize(zero_count as isize));
+        (a_co, b_co, c_co, d_co)
+    }
+    fn mul_small_signed(self: &Self, factor: i64) -> Self {
+        // self * factor, |factor| fits in one digit
+        let mut result: Self = self.digits_mul_small(factor.unsigned_abs() as Digit);
+        result.sign = (self.sign != (factor < 0)) && !result.is_digits_zero();
+        result
+    }
+    fn digits_gcd(a: Self, b: Self) -> Self {
+        // ref: Knuth, TAOCP Vol. 2, 4.5.2, Algorithm L (Lehmer)
+        let (mut a, mut b) = match Self::digits_cmp(&a, &b) {
+            Ordering::Less => (b, a),
+            _ => (a, b),
+        };
+        while !b.is_digits_zero() {
+            if a.digits.len() <= 2 {
+                let mut x: DoubleDigit = a.digits.iter().rev().fold(0, |value, &digit| {
+                    (value << DIGIT_BITS) | digit as DoubleDigit
+                });
+                let mut y: DoubleDigit = b.digits.iter().rev().fold(0, |value, &digit| {
+                    (value << DIGIT_BITS) | digit as DoubleDigit
+                });
+                while y != 0 {
+                    (x, y) = (y, x % y);
+                }
+                return Self::from_digits(false, vec![x as Digit, (x >> DIGIT_BITS) as Digit]);
+            }
+            let (a_co, b_co, c_co, d_co) = Self::lehmer_cofactors(&a, &b);
+            if b_co == 0 {
+                let (_, remainder) = Self::digits_div_rem(a, b.clone());
+                (a, b) = (b, remainder);
+            } else {
+                // the cofactors of each pair never have the same strict sign
+                let combine = |x_co: i64, y_co: i64| -> Self {
+                    let x_part: Self = a.digits_mul_small(x_co.unsigned_abs() as Digit);
+                    let y_part: Self = b.digits_mul_small(y_co.unsigned_abs() as Digit);
+                    if y_co <= 0 {
+                        Self::digits_sub(x_part, y_part)
+                    } else {
+                        Self::digits_sub(y_part, x_part)
+                    }
+                };
+                (a, b) = (combine(a_co, b_co), combine(c_co, d_co));
+            }
         }
-        self.value.tidy();
+        a
     }
-    pub fn add(a: Self, b: Self) -> Self {
-        let mut min_float_len: Integer = a.point;
-        let mut max_float_len: Integer = b.point;
-        let mut min_float_name: char = 'a';
-        if Integer::cmp(min_float_len.clone(), max_float_len.clone()) == Ordering::Greater {
-            let temp: Integer = min_float_len;
-            min_float_len = max_float_len;
-            max_float_len = temp;
-            min_float_name = 'b';
-        }
-        let mut min_float_value = if min_float_name == 'a' {
-            a.value.clone()
+    pub fn gcd(a: Self, b: Self) -> Self {
+        // always non-negative, gcd(0, 0) = 0
+        let result: Self = Self::digits_gcd(
+            Self::from_digits(false, a.digits),
+            Self::from_digits(false, b.digits),
+        );
+        result.validate();
+        result
+    }
+    pub fn lcm(a: Self, b: Self) -> Self {
+        // always non-negative, lcm(0, n) = 0
+        if a.is_digits_zero() || b.is_digits_zero() {
+            return Self::zero();
+        }
+        let a: Self = Self::from_digits(false, a.digits);
+        let b: Self = Self::from_digits(false, b.digits);
+        let gcd: Self = Self::digits_gcd(a.clone(), b.clone());
+        let result: Self = Self::digits_mul(Self::div_exact(a, gcd).unwrap(), b, 0);
+        result.validate();
+        result
+    }
+    pub fn extended_gcd(a: Self, b: Self) -> (Self, Self, Self) {
+        // (g, x, y) with a*x + b*y = g = gcd(a, b)
+        // lehmer steps as in digits_gcd, only the cofactor x of a is tracked, y comes from one
+        // exact division at the end
+        let a_sign: bool = a.sign;
+        let b_sign: bool = b.sign;
+        let a: Self = Self::from_digits(false, a.digits);
+        let b: Self = Self::from_digits(false, b.digits);
+        let (mut old_r, mut r) = (a.clone(), b.clone());
+        // old_r = a*old_x (mod b) and r = a*x (mod b)
+        let (mut old_x, mut x) = (Self::one(), Self::zero());
+        while !r.is_digits_zero() {
+            let (a_co, b_co, c_co, d_co) =
+                if old_r.digits.len() > 2 && Self::digits_cmp(&old_r, &r) != Ordering::Less {
+                    Self::lehmer_cofactors(&old_r, &r)
+                } else {
+                    (1, 0, 0, 1)
+                };
+            if b_co == 0 {
+                let (q, new_r) = Self::digits_div_rem(old_r, r.clone());
+                (old_r, r) = (r, new_r);
+                let new_x: Self = Self::sub(old_x, Self::mul(q, x.clone()));
+                (old_x, x) = (x, new_x);
+            } else {
+                let combine = |u: &Self, v: &Self, u_co: i64, v_co: i64| -> Self {
+                    Self::add(u.mul_small_signed(u_co), v.mul_small_signed(v_co))
+                };
+                (old_r, r) = (
+                    combine(&old_r, &r, a_co, b_co),
+                    combine(&old_r, &r, c_co, d_co),
+                );
+                (old_x, x) = (
+                    combine(&old_x, &x, a_co, b_co),
+                    combine(&old_x, &x, c_co, d_co),
+                );
+            }
+        }
+        let mut old_y: Self = if b.is_digits_zero() {
+            Self::zero()
         } else {
-            b.value.clone()
+            Self::div_exact(Self::sub(old_r.clone(), Self::mul(a, old_x.clone())), b).unwrap()
         };
-        min_float_value
-            .high_shift(
-                Integer::to_isize(Integer::digits_sub(max_float_len.clone(), min_float_len)) as u32,
-            );
-        Self::new(
-            max_float_len,
-            Integer::add(
-                min_float_value,
-                if min_float_name == 'a' {
-                    b.value.clone()
-                } else {
-                    a.value.clone()
-                },
-            ),
-        )
+        if a_sign {
+            old_x = Self::opposite(old_x);
+        }
+        if b_sign {
+            old_y = Self::opposite(old_y);
+        }
+        old_r.validate();
+        old_x.validate();
+        old_y.validate();
+        (old_r, old_x, old_y)
     }
-    pub fn sub(a: Self, b: Self) -> Self {
-        return Self::add(a, Self::opposite(b));
+    pub fn mod_inverse(a: Self, modulus: Self) -> Result<Self, ArithmeticError> {
+        // x in [0, |modulus|) with a*x = 1 (mod modulus)
+        if modulus.is_digits_zero() {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        let modulus: Self = Self::from_digits(false, modulus.digits);
+        let a: Self = Self::rem_euclid(a, modulus.clone())?;
+        let (g, x, _) = Self::extended_gcd(a, modulus.clone());
+        if g.digits != [1] {
+            return Err(ArithmeticError::NotInvertible);
+        }
+        Self::rem_euclid(x, modulus)
     }
 
-    fn high_shift(self: &mut Self, times: u32) {
-        self.point = Integer::sub(self.point.clone(), Integer::from_isize(times as isize));
+    fn digits_nth_root(n: Self, k: u32) -> Self {
+        // floor(n^(1/k)) by newton iteration, n: magnitude only, k >= 2
+        if n.is_digits_zero() {
+            return Self::zero();
+        }
+        // start from 2^ceil(bits / k), which is never below the root
+        let bits: usize = n.bit_length().div_ceil(k as usize);
+        let mut x: Self = Self::zero();
+        x.digits = vec![0; bits / DIGIT_BITS as usize + 1];
+        x.digits[bits / DIGIT_BITS as usize] = 1 << (bits % DIGIT_BITS as usize);
+        x.tidy();
+        let k_integer: Self = Self::from_digits(false, vec![k]);
+        loop {
+            // y = ((k - 1) * x + n / x^(k - 1)) / k
+            let (quotient, _) = Self::digits_div_rem(n.clone(), Self::pow_u32(x.clone(), k - 1));
+            let (y, _) = Self::digits_div_rem(
+                Self::digits_add(x.digits_mul_small(k - 1), quotient),
+                k_integer.clone(),
+            );
+            if Self::digits_cmp(&y, &x) != Ordering::Less {
+                return x;
+            }
+            x = y;
+        }
     }
-    fn low_shift(self: &mut Self, times: u32) {
-        self.point = Integer::add(self.point.clone(), Integer::from_isize(times as isize));
+    pub fn nth_root(n: Self, k: u32) -> Result<(Self, Self), ArithmeticError> {
+        // (root, remainder) with n = root^k + remainder, the root rounds toward zero
+        if k == 0 {
+            // n^(1/0)
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        if n.sign && k.is_multiple_of(2) {
+            return Err(ArithmeticError::NegativeRoot);
+        }
+        if k == 1 || n.is_digits_zero() {
+            return Ok((n, Self::zero()));
+        }
+        if k as usize >= n.bit_length() {
+            // |n| < 2^k, so the root is 1 (or -1 for an odd k) and no power is built
+            let root: Self = if n.sign { Self::from(-1) } else { Self::one() };
+            let remainder: Self = Self::sub(n, root.clone());
+            return Ok((root, remainder));
+        }
+        let mut root: Self = Self::digits_nth_root(Self::from_digits(false, n.digits.clone()), k);
+        root.sign = n.sign && !root.is_digits_zero();
+        root.validate();
+        let remainder: Self = Self::sub(n, Self::pow_u32(root.clone(), k));
+        return Ok((root, remainder));
     }
-
-    pub fn mul(a: Self, b: Self) -> Self {
-        Self::new(
-            Integer::add(a.point, b.point),
-            Integer::mul(a.value, b.value),
-        )
+    pub fn isqrt(n: Self) -> Result<(Self, Self), ArithmeticError> {
+        Self::nth_root(n, 2)
+    }
+    pub fn is_perfect_square(n: Self) -> bool {
+        if n.sign {
+            return false;
+        }
+        // squares can only end in these 12 residues mod 64
+        if (0x0202_0212_0203_0213_u64 >> (n.digits[0] & 63)) & 1 == 0 {
+            return false;
+        }
+        let (_, remainder) = Self::isqrt(n).unwrap();
+        remainder.is_digits_zero()
+    }
+    pub fn perfect_power(n: Self) -> Option<(Self, u32)> {
+        // (base, exp) with n = base^exp and exp >= 2 as large as possible
+        // 0, 1 and -1 are powers of every exponent, so they are not reported
+        let magnitude: Self = Self::from_digits(false, n.digits.clone());
+        if magnitude.digits.len() == 1 && magnitude.digits[0] <= 1 {
+            return None;
+        }
+        // a k-th power needs a k-th root of at least 2
+        let max_exp: u32 = magnitude.bit_length() as u32;
+        let mut p: u32 = if n.sign { 3 } else { 2 };
+        while p <= max_exp {
+            let is_prime: bool = (2..p)
+                .take_while(|d| d * d <= p)
+                .all(|d| !p.is_multiple_of(d));
+            if is_prime {
+                let mut root: Self = Self::digits_nth_root(magnitude.clone(), p);
+                if Self::digits_cmp(&Self::pow_u32(root.clone(), p), &magnitude) == Ordering::Equal
+                {
+                    // p is odd for a negative n, so the root keeps the sign
+                    root.sign = n.sign;
+                    return match Self::perfect_power(root.clone()) {
+                        Some((base, exp)) => Some((base, exp * p)),
+                        None => Some((root, p)),
+                    };
+                }
+            }
+            p += if p == 2 { 1 } else { 2 };
+        }
+        None
     }
-}
-
-/* Symbol */
-
-// #[derive(Clone)]
-// pub struct Symbol {
-//     redefined: bool,
-//     symbol: String,
-// }
 
-// impl Symbol {
-//     pub fn new(redefined: bool, symbol: String) -> Self {
-//         Self {
-//             redefined: redefined,
-//             symbol: symbol,
-//         }
-//     }
-//     fn get_value(self: Self) -> Integer {}
-// }
+    fn digits_rem_small(self: &Self, divisor: Digit) -> Digit {
+        // |n| mod divisor, divisor != 0
+        self.digits.iter().rev().fold(0, |remainder, &digit| {
+            (((remainder as DoubleDigit) << DIGIT_BITS | digit as DoubleDigit)
+                % divisor as DoubleDigit) as Digit
+        })
+    }
+    fn jacobi(a: Self, n: Self) -> i32 {
+        // jacobi symbol (a/n), n odd and positive
+        let mut a: Self = Self::mod_floor(a, n.clone()).unwrap();
+        let mut n: Self = n;
+        let mut result: i32 = 1;
+        while !a.is_digits_zero() {
+            let twos: usize = a.trailing_zeros().unwrap();
+            a = a.digits_shr_bits(twos);
+            // (2/n) = -1 for n = 3, 5 mod 8
+            if twos % 2 == 1 && matches!(n.digits[0] & 7, 3 | 5) {
+                result = -result;
+            }
+            // quadratic reciprocity
+            if a.digits[0] & 3 == 3 && n.digits[0] & 3 == 3 {
+                result = -result;
+            }
+            std::mem::swap(&mut a, &mut n);
+            a = Self::digits_div_rem(a, n.clone()).1;
+        }
+        if n == Self::one() {
+            result
+        } else {
+            0
+        }
+    }
+    fn is_strong_probable_prime(n: &Self, base: Self) -> bool {
+        // miller-rabin to the given base, n odd and > 2
+        let n_minus_one: Self = Self::sub(n.clone(), Self::one());
+        let s: usize = n_minus_one.trailing_zeros().unwrap();
+        let d: Self = n_minus_one.digits_shr_bits(s);
+        let mut x: Self = Self::pow_mod(base, d, n.clone()).unwrap();
+        if x == Self::one() || x == n_minus_one {
+            return true;
+        }
+        for _ in 1..s {
+            x = Self::digits_div_rem(Self::mul(x.clone(), x), n.clone()).1;
+            if x == n_minus_one {
+                return true;
+            }
+        }
+        false
+    }
+    fn is_strong_lucas_probable_prime(n: &Self) -> bool {
+        // ref: Baillie & Wagstaff, "Lucas Pseudoprimes", selfridge's parameters
+        // n odd, > 2 and not a perfect square
+        let mut d: i64 = 5;
+        while Self::jacobi(Self::from(d), n.clone()) != -1 {
+            d = if d > 0 { -(d + 2) } else { -d + 2 };
+        }
+        let p: Self = Self::one();
+        let q: Self = Self::from((1 - d) / 4);
+        let d: Self = Self::from(d);
+        let reduce = |x: Self| -> Self { Self::mod_floor(x, n.clone()).unwrap() };
+        let half = |x: Self| -> Self {
+            // x / 2 mod n
+            let x: Self = if x.digits[0] & 1 == 1 {
+                Self::add(x, n.clone())
+            } else {
+                x
+            };
+            x.digits_shr_bits(1)
+        };
+        // n + 1 = k * 2^s
+        let n_plus_one: Self = Self::add(n.clone(), Self::one());
+        let s: usize = n_plus_one.trailing_zeros().unwrap();
+        let k: Self = n_plus_one.digits_shr_bits(s);
+        // (u, v, q^i) for i = 1, then doubled and stepped along the bits of k
+        let mut u: Self = Self::one();
+        let mut v: Self = p.clone();
+        let mut q_power: Self = reduce(q.clone());
+        for bit in k.digits_bits().into_iter().skip(1) {
+            u = reduce(Self::mul(u, v.clone()));
+            v = reduce(Self::sub(
+                Self::mul(v.clone(), v),
+                Self::shl(q_power.clone(), 1),
+            ));
+            q_power = reduce(Self::mul(q_power.clone(), q_power));
+            if bit {
+                let next_u: Self = half(Self::add(Self::mul(p.clone(), u.clone()), v.clone()));
+                let next_v: Self = half(reduce(Self::add(
+                    Self::mul(d.clone(), u),
+                    Self::mul(p.clone(), v),
+                )));
+                u = reduce(next_u);
+                v = reduce(next_v);
+                q_power = reduce(Self::mul(q_power, q.clone()));
+            }
+        }
+        if u.is_digits_zero() || v.is_digits_zero() {
+            return true;
+        }
+        for _ in 1..s {
+            v = reduce(Self::sub(
+                Self::mul(v.clone(), v),
+                Self::shl(q_power.clone(), 1),
+            ));
+            if v.is_digits_zero() {
+                return true;
+            }
+            q_power = reduce(Self::mul(q_power.clone(), q_power));
+        }
+        false
+    }
+    pub fn is_probable_prime(n: Self) -> bool {
+        // exact below 2^64, baillie-psw above, which has no known counterexample
+        if n.sign {
+            return false;
+        }
+        if let Some(value) = n
+            .to_u128_magnitude()
+            .and_then(|value| u64::try_from(value).ok())
+        {
+            return is_prime_u64(value);
+        }
+        for prime in small_primes(1000) {
+            if n.digits_rem_small(prime) == 0 {
+                return false;
+            }
+        }
+        if !Self::is_strong_probable_prime(&n, Self::from(2)) {
+            return false;
+        }
+        // the lucas test never finds its parameter for a square
+        if Self::is_perfect_square(n.clone()) {
+            return false;
+        }
+        Self::is_strong_lucas_probable_prime(&n)
+    }
+    pub fn next_prime(n: Self) -> Self {
+        // the smallest prime > n
+        if Self::cmp(n.clone(), Self::from(2)) == Ordering::Less {
+            return Self::from(2);
+        }
+        let mut candidate: Self = Self::add(n, Self::one());
+        if candidate.digits[0] & 1 == 0 {
+            if candidate == Self::from(2) {
+                return candidate;
+            }
+            candidate = Self::add(candidate, Self::one());
+        }
+        while !Self::is_probable_prime(candidate.clone()) {
+            candidate = Self::add(candidate, Self::from(2));
+        }
+        candidate
+    }
+    pub fn prev_prime(n: Self) -> Option<Self> {
+        // the largest prime < n, None when n <= 2
+        if Self::cmp(n.clone(), Self::from(2)) != Ordering::Greater {
+            return None;
+        }
+        if n == Self::from(3) {
+            return Some(Self::from(2));
+        }
+        let mut candidate: Self = Self::sub(n, Self::one());
+        if candidate.digits[0] & 1 == 0 {
+            candidate = Self::sub(candidate, Self::one());
+        }
+        while !Self::is_probable_prime(candidate.clone()) {
+            candidate = Self::sub(candidate, Self::from(2));
+        }
+        Some(candidate)
+    }
+
+    fn pollard_brent(n: &Self, c: Self) -> Option<Self> {
+        // ref: Brent, "An Improved Monte Carlo Factorization Algorithm"
+        // a non-trivial factor of the odd composite n, or None when this c fails
+        const BATCH: usize = 128;
+        let step = |y: Self| -> Self {
+            Self::digits_div_rem(Self::add(Self::mul(y.clone(), y), c.clone()), n.clone()).1
+        };
+        let distance = |x: &Self, y: &Self| -> Self {
+            Self::from_digits(false, Self::sub(x.clone(), y.clone()).digits)
+        };
+        let mut y: Self = Self::from(2);
+        let mut x: Self = y.clone();
+        let mut saved_y: Self = y.clone();
+        let mut product: Self = Self::one();
+        let mut g: Self = Self::one();
+        let mut r: usize = 1;
+        while g == Self::one() {
+            x = y.clone();
+            for _ in 0..r {
+                y = step(y);
+            }
+            let mut k: usize = 0;
+            while k < r && g == Self::one() {
+                // gcd once per batch, on the product of the distances
+                saved_y = y.clone();
+                for _ in 0..min(BATCH, r - k) {
+                    y = step(y);
+                    product =
+                        Self::digits_div_rem(Self::mul(product, distance(&x, &y)), n.clone()).1;
+                }
+                g = Self::gcd(product.clone(), n.clone());
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == *n {
+            // the batch overshot, redo it one step at a time
+            loop {
+                saved_y = step(saved_y);
+                g = Self::gcd(distance(&x, &saved_y), n.clone());
+                if g != Self::one() {
+                    break;
+                }
+            }
+        }
+        if g == *n {
+            None
+        } else {
+            Some(g)
+        }
+    }
+    pub fn factor(n: Self) -> BTreeMap<Self, u32> {
+        // prime -> exponent for |n|, empty for 0 and 1
+        // trial division by small primes first, then pollard-brent rho on what is left
+        let mut factors: BTreeMap<Self, u32> = BTreeMap::new();
+        let mut rest: Self = Self::from_digits(false, n.digits);
+        if rest.is_digits_zero() {
+            return factors;
+        }
+        for prime in small_primes(TRIAL_DIVISION_LIMIT) {
+            if rest.digits.len() == 1
+                && (prime as DoubleDigit).pow(2) > rest.digits[0] as DoubleDigit
+            {
+                break;
+            }
+            while rest.digits_rem_small(prime) == 0 {
+                rest = Self::digits_short_div(rest, prime).0;
+                *factors.entry(Self::from(prime)).or_insert(0) += 1;
+            }
+        }
+        let mut composites: Vec<Self> = vec![rest];
+        while let Some(m) = composites.pop() {
+            if m == Self::one() {
+                continue;
+            }
+            if Self::is_probable_prime(m.clone()) {
+                *factors.entry(m).or_insert(0) += 1;
+                continue;
+            }
+            if let Some((base, exp)) = Self::perfect_power(m.clone()) {
+                composites.extend(std::iter::repeat_n(base, exp as usize));
+                continue;
+            }
+            let mut c: Self = Self::one();
+            loop {
+                if let Some(divisor) = Self::pollard_brent(&m, c.clone()) {
+                    composites.push(Self::digits_div_rem(m, divisor.clone()).0);
+                    composites.push(divisor);
+                    break;
+                }
+                c = Self::add(c, Self::one());
+            }
+        }
+        factors
+    }
+
+    pub fn bit_length(self: &Self) -> usize {
+        // bits of |n|, 0 for 0
+        let top: Digit = self.digits[self.digits.len() - 1];
+        (self.digits.len() - 1) * DIGIT_BITS as usize + (DIGIT_BITS - top.leading_zeros()) as usize
+    }
+    pub fn count_ones(self: &Self) -> usize {
+        // ones in |n|, a negative n has infinitely many in two's complement
+        self.digits
+            .iter()
+            .map(|digit| digit.count_ones() as usize)
+            .sum()
+    }
+    pub fn trailing_zeros(self: &Self) -> Option<usize> {
+        // the same for n and -n, None for 0
+        let index: usize = self.digits.iter().position(|&digit| digit != 0)?;
+        Some(index * DIGIT_BITS as usize + self.digits[index].trailing_zeros() as usize)
+    }
+    fn to_twos_complement(self: &Self, len: usize) -> Vec<Digit> {
+        // len digits of two's complement, len must leave room for the sign bit
+        let mut magnitude: Vec<Digit> = if self.sign {
+            // -n = !(n - 1)
+            Self::digits_sub(Self::from_digits(false, self.digits.clone()), Self::one()).digits
+        } else {
+            self.digits.clone()
+        };
+        magnitude.resize(len, 0);
+        if self.sign {
+            for digit in magnitude.iter_mut() {
+                *digit = !*digit;
+            }
+        }
+        magnitude
+    }
+    fn from_twos_complement(digits: Vec<Digit>) -> Self {
+        let sign: bool = digits
+            .last()
+            .is_some_and(|&top| top >> (DIGIT_BITS - 1) == 1);
+        if !sign {
+            return Self::from_digits(false, digits);
+        }
+        let inverted: Vec<Digit> = digits.iter().map(|&digit| !digit).collect();
+        let mut result: Self = Self::digits_add(Self::from_digits(false, inverted), Self::one());
+        result.sign = true;
+        result.validate();
+        result
+    }
+    fn bitwise(a: Self, b: Self, operation: fn(Digit, Digit) -> Digit) -> Self {
+        let len: usize = max(a.digits.len(), b.digits.len()) + 1;
+        let a_digits: Vec<Digit> = a.to_twos_complement(len);
+        let b_digits: Vec<Digit> = b.to_twos_complement(len);
+        let result: Self = Self::from_twos_complement(
+            a_digits
+                .iter()
+                .zip(b_digits.iter())
+                .map(|(&a_digit, &b_digit)| operation(a_digit, b_digit))
+                .collect(),
+        );
+        result.validate();
+        result
+    }
+    pub fn and(a: Self, b: Self) -> Self {
+        Self::bitwise(a, b, |a_digit, b_digit| a_digit & b_digit)
+    }
+    pub fn or(a: Self, b: Self) -> Self {
+        Self::bitwise(a, b, |a_digit, b_digit| a_digit | b_digit)
+    }
+    pub fn xor(a: Self, b: Self) -> Self {
+        Self::bitwise(a, b, |a_digit, b_digit| a_digit ^ b_digit)
+    }
+    pub fn not(n: Self) -> Self {
+        // !n = -n - 1
+        let result: Self = Self::sub(Self::opposite(n), Self::one());
+        result.validate();
+        result
+    }
+    fn digits_shl_bits(self: &Self, bits: usize) -> Self {
+        // magnitude only, * 2^bits
+        let bit_shift: u32 = (bits % DIGIT_BITS as usize) as u32;
+        let mut new_digits: Vec<Digit> = vec![0; bits / DIGIT_BITS as usize];
+        let mut carry: Digit = 0;
+        for &digit in self.digits.iter() {
+            new_digits.push((digit << bit_shift) | carry);
+            carry = if bit_shift == 0 {
+                0
+            } else {
+                digit >> (DIGIT_BITS - bit_shift)
+            };
+        }
+        new_digits.push(carry);
+        Self::from_digits(false, new_digits)
+    }
+    pub fn shl(n: Self, bits: usize) -> Self {
+        // n * 2^bits
+        let mut result: Self = n.digits_shl_bits(bits);
+        result.sign = n.sign && !result.is_digits_zero();
+        result.validate();
+        result
+    }
+    pub fn shr(n: Self, bits: usize) -> Self {
+        // floor(n / 2^bits), as an arithmetic shift in two's complement
+        if !n.sign {
+            let result: Self = n.digits_shr_bits(bits);
+            result.validate();
+            return result;
+        }
+        // -(((|n| - 1) >> bits) + 1)
+        let magnitude: Self = Self::digits_sub(Self::from_digits(false, n.digits), Self::one());
+        let mut result: Self = Self::digits_add(magnitude.digits_shr_bits(bits), Self::one());
+        result.sign = true;
+        result.validate();
+        result
+    }
+    fn twos_complement_digit(self: &Self, position: usize) -> Digit {
+        // digit `position` of the two's complement form, the others are never built
+        // -m = !(m - 1): zeros below the lowest nonzero digit d of m, then -d, then !digit
+        let digit: Digit = self.digits.get(position).copied().unwrap_or(0);
+        if !self.sign {
+            return digit;
+        }
+        if position >= self.digits.len() {
+            return Digit::MAX;
+        }
+        let lowest: usize = self.digits.iter().position(|&digit| digit != 0).unwrap();
+        match position.cmp(&lowest) {
+            Ordering::Less => 0,
+            Ordering::Equal => digit.wrapping_neg(),
+            Ordering::Greater => !digit,
+        }
+    }
+    pub fn test_bit(self: &Self, index: usize) -> bool {
+        // bit `index` of the two's complement form, the sign past the top digit
+        let digit: Digit = self.twos_complement_digit(index / DIGIT_BITS as usize);
+        (digit >> (index % DIGIT_BITS as usize)) & 1 == 1
+    }
+    pub fn set_bit(self: &mut Self, index: usize, value: bool) {
+        // sets bit `index` of the two's complement form, a change adds or removes 2^index
+        if self.test_bit(index) == value {
+            return;
+        }
+        let bit: Self = Self::shl(Self::one(), index);
+        if value {
+            self.add_assign(&bit);
+        } else {
+            self.sub_assign(&bit);
+        }
+        self.validate();
+    }
+}
+
+forward_in_place_operator!(Integer, Add, add, AddAssign, add_assign);
+forward_in_place_operator!(Integer, Sub, sub, SubAssign, sub_assign);
+forward_binary_operator!(Integer, Mul, mul, MulAssign, mul_assign);
+forward_negative_operator!(Integer);
+forward_binary_operator!(Integer, BitAnd, bitand, BitAndAssign, bitand_assign, and);
+forward_binary_operator!(Integer, BitOr, bitor, BitOrAssign, bitor_assign, or);
+forward_binary_operator!(Integer, BitXor, bitxor, BitXorAssign, bitxor_assign, xor);
+impl Not for Integer {
+    type Output = Integer;
+    fn not(self) -> Integer {
+        Integer::not(self)
+    }
+}
+impl Not for &Integer {
+    type Output = Integer;
+    fn not(self) -> Integer {
+        Integer::not(self.clone())
+    }
+}
+impl Shl<usize> for Integer {
+    type Output = Integer;
+    fn shl(self, bits: usize) -> Integer {
+        Integer::shl(self, bits)
+    }
+}
+impl Shl<usize> for &Integer {
+    type Output = Integer;
+    fn shl(self, bits: usize) -> Integer {
+        Integer::shl(self.clone(), bits)
+    }
+}
+impl ShlAssign<usize> for Integer {
+    fn shl_assign(&mut self, bits: usize) {
+        *self = Integer::shl(std::mem::replace(self, Integer::zero()), bits);
+    }
+}
+impl Shr<usize> for Integer {
+    type Output = Integer;
+    fn shr(self, bits: usize) -> Integer {
+        Integer::shr(self, bits)
+    }
+}
+impl Shr<usize> for &Integer {
+    type Output = Integer;
+    fn shr(self, bits: usize) -> Integer {
+        Integer::shr(self.clone(), bits)
+    }
+}
+impl ShrAssign<usize> for Integer {
+    fn shr_assign(&mut self, bits: usize) {
+        *self = Integer::shr(std::mem::replace(self, Integer::zero()), bits);
+    }
+}
+
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(Ord::cmp(self, other))
+    }
+}
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Integer::cmp_ref(self, other)
+    }
+}
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimal: String = Self::digits_to_radix(self, DECIMAL_RADIX as u32)
+            .iter()
+            .map(|&n| from_digit(n as u32, DECIMAL_RADIX as u32).unwrap())
+            .collect();
+        f.pad_integral(!self.sign, "", &decimal)
+    }
+}
+impl fmt::Debug for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+impl FromStr for Integer {
+    type Err = ParseNumberError;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Self::parse(string)
+    }
+}
+
+macro_rules! impl_integer_from_signed {
+    ($($primitive:ty),*) => {
+        $(
+            impl From<$primitive> for Integer {
+                fn from(number: $primitive) -> Self {
+                    Self::from_u128(number < 0, (number as i128).unsigned_abs())
+                }
+            }
+        )*
+    };
+}
+macro_rules! impl_integer_from_unsigned {
+    ($($primitive:ty),*) => {
+        $(
+            impl From<$primitive> for Integer {
+                fn from(number: $primitive) -> Self {
+                    Self::from_u128(false, number as u128)
+                }
+            }
+        )*
+    };
+}
+impl_integer_from_signed!(i8, i16, i32, i64, i128, isize);
+impl_integer_from_unsigned!(u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_primitive_try_from_integer {
+    ($($primitive:ty),*) => {
+        $(
+            impl TryFrom<&Integer> for $primitive {
+                type Error = TryFromIntegerError;
+                fn try_from(n: &Integer) -> Result<Self, Self::Error> {
+                    let magnitude: u128 = n.to_u128_magnitude().ok_or(TryFromIntegerError)?;
+                    #[allow(unused_comparisons)]
+                    let signed: bool = <$primitive>::MIN < 0;
+                    if !n.sign {
+                        if magnitude > <$primitive>::MAX as u128 {
+                            return Err(TryFromIntegerError);
+                        }
+                        Ok(magnitude as $primitive)
+                    } else if signed && magnitude <= (<$primitive>::MIN as i128).unsigned_abs() {
+                        Ok((magnitude as i128).wrapping_neg() as $primitive)
+                    } else {
+                        Err(TryFromIntegerError)
+                    }
+                }
+            }
+            impl TryFrom<Integer> for $primitive {
+                type Error = TryFromIntegerError;
+                fn try_from(n: Integer) -> Result<Self, Self::Error> {
+                    <$primitive>::try_from(&n)
+                }
+            }
+        )*
+    };
+}
+impl_primitive_try_from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl Encoding for Integer {
+    const KIND: u8 = 0;
+    fn encode_body(self: &Self, out: &mut Vec<u8>) {
+        let count: usize = if self.is_zero() { 0 } else { self.digits.len() };
+        write_varint(out, (count as u64) << 1 | self.sign as u64);
+        for &digit in self.digits[..count].iter() {
+            write_varint(out, digit as u64);
+        }
+    }
+    fn decode_body(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let start: usize = reader.position();
+        let header: u64 = reader.read_varint()?;
+        let sign: bool = header & 1 == 1;
+        // every limb takes at least one byte, so a count beyond the input is cut short
+        let count: usize = (header >> 1) as usize;
+        if count > reader.remaining() {
+            return Err(DecodeError::new(
+                DecodeErrorKind::UnexpectedEnd,
+                reader.position() + reader.remaining(),
+            ));
+        }
+        let mut digits: Vec<Digit> = Vec::with_capacity(count);
+        for _ in 0..count {
+            let position: usize = reader.position();
+            let digit: u64 = reader.read_varint()?;
+            digits.push(
+                Digit::try_from(digit)
+                    .map_err(|_| DecodeError::new(DecodeErrorKind::Overflow, position))?,
+            );
+        }
+        if digits.last() == Some(&0) || (count == 0 && sign) {
+            return Err(DecodeError::new(DecodeErrorKind::NonCanonical, start));
+        }
+        Ok(Self::from_digits(sign, digits))
+    }
+    fn to_json_value(self: &Self) -> Json {
+        Json::string(self.to_string())
+    }
+    fn from_json_value(json: &Json) -> Result<Self, DecodeError> {
+        // + 1 skips the opening quote
+        Self::parse(json.as_str()?).map_err(|error| {
+            DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                json.position + 1 + error.position(),
+            )
+        })
+    }
+}
+
+/* ModInt */
+
+// the modulus shared by a family of residues
+// an odd modulus keeps its residues in montgomery form, x * R mod m with R = 2^(32*limbs)
+#[derive(Debug)]
+pub struct ModContext {
+    modulus: Integer,
+    montgomery: Option<Montgomery>,
+}
+
+#[derive(Debug)]
+struct Montgomery {
+    limbs: usize,
+    // -m^-1 mod 2^32
+    m_prime: Digit,
+    // R^2 mod m, to move into montgomery form
+    r_squared: Integer,
+}
+
+#[derive(Clone)]
+pub struct ModInt {
+    value: Integer,
+    context: Rc<ModContext>,
+}
+
+impl ModContext {
+    pub fn new(modulus: Integer) -> Result<Rc<Self>, ArithmeticError> {
+        // residues mod |modulus|
+        if modulus.is_digits_zero() {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        let modulus: Integer = Integer::from_digits(false, modulus.digits);
+        let montgomery: Option<Montgomery> =
+            if modulus.digits[0] & 1 == 1 && modulus != Integer::one() {
+                let limbs: usize = modulus.digits.len();
+                // newton iteration for m^-1 mod 2^32, every step doubles the correct bits
+                let mut inverse: Digit = 1;
+                for _ in 0..5 {
+                    inverse = inverse
+                        .wrapping_mul(2_u32.wrapping_sub(modulus.digits[0].wrapping_mul(inverse)));
+                }
+                let r_squared: Integer = Integer::digits_div_rem(
+                    Integer::shl(Integer::one(), 2 * limbs * DIGIT_BITS as usize),
+                    modulus.clone(),
+                )
+                .1;
+                Some(Montgomery {
+                    limbs: limbs,
+                    m_prime: inverse.wrapping_neg(),
+                    r_squared: r_squared,
+                })
+            } else {
+                None
+            };
+        Ok(Rc::new(Self {
+            modulus: modulus,
+            montgomery: montgomery,
+        }))
+    }
+    pub fn modulus(self: &Self) -> &Integer {
+        &self.modulus
+    }
+
+    fn reduce(self: &Self, value: Integer) -> Integer {
+        // [0, m)
+        Integer::mod_floor(value, self.modulus.clone()).unwrap()
+    }
+    fn montgomery_reduce(self: &Self, montgomery: &Montgomery, t: Integer) -> Integer {
+        // ref: Montgomery, "Modular Multiplication Without Trial Division"
+        // t * R^-1 mod m, for 0 <= t < m * R
+        let n: usize = montgomery.limbs;
+        let m: &[Digit] = &self.modulus.digits;
+        let mut t: Vec<Digit> = t.digits;
+        t.resize(2 * n + 1, 0);
+        for i in 0..n {
+            // adding u * m * 2^(32*i) clears digit i
+            let u: Digit = t[i].wrapping_mul(montgomery.m_prime);
+            let mut carry: DoubleDigit = 0;
+            for (j, &m_digit) in m.iter().enumerate() {
+                let digit: DoubleDigit =
+                    t[i + j] as DoubleDigit + u as DoubleDigit * m_digit as DoubleDigit + carry;
+                t[i + j] = digit as Digit;
+                carry = digit >> DIGIT_BITS;
+            }
+            let mut k: usize = i + n;
+            while carry > 0 {
+                let digit: DoubleDigit = t[k] as DoubleDigit + carry;
+                t[k] = digit as Digit;
+                carry = digit >> DIGIT_BITS;
+                k += 1;
+            }
+        }
+        let mut result: Integer = Integer::from_digits(false, t.split_off(n));
+        if Integer::digits_cmp(&result, &self.modulus) != Ordering::Less {
+            result.sub_assign(&self.modulus);
+        }
+        result
+    }
+    fn to_form(self: &Self, value: Integer) -> Integer {
+        // standard residue -> internal form
+        let value: Integer = self.reduce(value);
+        match &self.montgomery {
+            Some(montgomery) => self.montgomery_reduce(
+                montgomery,
+                Integer::mul(value, montgomery.r_squared.clone()),
+            ),
+            None => value,
+        }
+    }
+    fn from_form(self: &Self, value: &Integer) -> Integer {
+        // internal form -> standard residue
+        match &self.montgomery {
+            Some(montgomery) => self.montgomery_reduce(montgomery, value.clone()),
+            None => value.clone(),
+        }
+    }
+    fn mul_form(self: &Self, a: &Integer, b: &Integer) -> Integer {
+        let mut product: Integer = Integer::zero();
+        Integer::mul_into(a, b, &mut product);
+        match &self.montgomery {
+            Some(montgomery) => self.montgomery_reduce(montgomery, product),
+            None => Integer::digits_div_rem(product, self.modulus.clone()).1,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl ModInt {
+    pub fn new(value: Integer, context: &Rc<ModContext>) -> Self {
+        Self {
+            value: context.to_form(value),
+            context: Rc::clone(context),
+        }
+    }
+    pub fn zero(context: &Rc<ModContext>) -> Self {
+        Self::new(Integer::zero(), context)
+    }
+    pub fn one(context: &Rc<ModContext>) -> Self {
+        Self::new(Integer::one(), context)
+    }
+    pub fn value(self: &Self) -> Integer {
+        // the residue in [0, m)
+        self.context.from_form(&self.value)
+    }
+    pub fn modulus(self: &Self) -> &Integer {
+        self.context.modulus()
+    }
+    pub fn context(self: &Self) -> &Rc<ModContext> {
+        &self.context
+    }
+
+    fn check_context(self: &Self, other: &Self) {
+        assert!(
+            Rc::ptr_eq(&self.context, &other.context)
+                || self.context.modulus == other.context.modulus,
+            "ModInt: the residues have different moduli"
+        );
+    }
+    pub fn add_assign(self: &mut Self, rhs: &Self) {
+        // both forms are linear, so add and sub work on them directly
+        self.check_context(rhs);
+        self.value.add_assign(&rhs.value);
+        if Integer::digits_cmp(&self.value, &self.context.modulus) != Ordering::Less {
+            self.value.sub_assign(&self.context.modulus);
+        }
+    }
+    pub fn sub_assign(self: &mut Self, rhs: &Self) {
+        self.check_context(rhs);
+        self.value.sub_assign(&rhs.value);
+        if self.value.sign {
+            self.value.add_assign(&self.context.modulus);
+        }
+    }
+    pub fn mul_assign(self: &mut Self, rhs: &Self) {
+        self.check_context(rhs);
+        self.value = self.context.mul_form(&self.value, &rhs.value);
+    }
+    pub fn add(a: Self, b: Self) -> Self {
+        let mut result: Self = a;
+        result.add_assign(&b);
+        result
+    }
+    pub fn sub(a: Self, b: Self) -> Self {
+        let mut result: Self = a;
+        result.sub_assign(&b);
+        result
+    }
+    pub fn mul(a: Self, b: Self) -> Self {
+        let mut result: Self = a;
+        result.mul_assign(&b);
+        result
+    }
+    pub fn opposite(n: Self) -> Self {
+        let mut result: Self = Self::zero(&n.context);
+        result.sub_assign(&n);
+        result
+    }
+    pub fn inverse(n: Self) -> Result<Self, ArithmeticError> {
+        let inverse: Integer = Integer::mod_inverse(n.value(), n.context.modulus.clone())?;
+        Ok(Self::new(inverse, &n.context))
+    }
+    pub fn div(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        a.check_context(&b);
+        Ok(Self::mul(a, Self::inverse(b)?))
+    }
+    pub fn pow(base: Self, exp: Integer) -> Result<Self, ArithmeticError> {
+        // a negative exponent raises the inverse
+        let base: Self = if exp.sign { Self::inverse(base)? } else { base };
+        let mut result: Self = Self::one(&base.context);
+        for bit in exp.digits_bits() {
+            result.value = result.context.mul_form(&result.value, &result.value);
+            if bit {
+                result.mul_assign(&base);
+            }
+        }
+        Ok(result)
+    }
+}
+
+forward_in_place_operator!(ModInt, Add, add, AddAssign, add_assign);
+forward_in_place_operator!(ModInt, Sub, sub, SubAssign, sub_assign);
+forward_in_place_operator!(ModInt, Mul, mul, MulAssign, mul_assign);
+forward_negative_operator!(ModInt);
+
+impl PartialEq for ModInt {
+    fn eq(&self, other: &Self) -> bool {
+        self.context.modulus == other.context.modulus && self.value == other.value
+    }
+}
+impl Eq for ModInt {}
+impl fmt::Display for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value(), f)
+    }
+}
+impl fmt::Debug for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value(), self.context.modulus)
+    }
+}
+
+/* Float */
+
+const F64_MANTISSA_BITS: u32 = 52; // stored bits, the hidden one excluded
+const F64_MIN_EXPONENT: i64 = -1074; // 2^-1074 is the smallest subnormal
+const F64_MIN_DECIMAL_EXPONENT: isize = -324; // below 10^-325 everything rounds to zero
+const F64_MAX_DECIMAL_EXPONENT: isize = 308; // from 10^309 everything overflows
+const EXACT_POWER_BITS: usize = 1 << 22; // exact powers beyond this go through exp and ln
+const ZIV_MAX_DOUBLINGS: u32 = 20; // ziv_round gives up this many doublings past its first try
+const FLOAT_MAX_POINT: usize = 999_999_999; // parsed points stay within +-this, their sums fit a u32
+const FORMAT_MAX_ZEROS: usize = 4096; // more zeros than this are written as an exponent
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    SignificantDigits(NonZeroUsize), // digits counted from the first nonzero one
+    DecimalPlaces(isize), // digits after the point, negative rounds to tens, hundreds, ...
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    HalfEven,     // to nearest, ties to the even digit
+    HalfUp,       // to nearest, ties away from zero
+    Floor,        // toward -inf
+    Ceiling,      // toward +inf
+    Truncate,     // toward zero
+    AwayFromZero, // away from zero
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Context {
+    precision: Precision,
+    rounding: RoundingMode,
+}
+
+// 34 significant digits, ties to even, as in the IEEE-754 decimal128 format
+const DEFAULT_CONTEXT: Context = Context {
+    precision: Precision::SignificantDigits(NonZeroUsize::new(34).unwrap()),
+    rounding: RoundingMode::HalfEven,
+};
+thread_local! {
+    // what Float::add, sub, mul and their operators round to on this thread
+    static CURRENT_CONTEXT: Cell<Context> = const { Cell::new(DEFAULT_CONTEXT) };
+}
+
+// puts the previous context back when a scope ends, panicking or not
+struct ContextGuard {
+    previous: Context,
+}
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        Context::set_current(self.previous);
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        DEFAULT_CONTEXT
+    }
+}
+impl Context {
+    pub fn new(precision: Precision, rounding: RoundingMode) -> Self {
+        Self {
+            precision: precision,
+            rounding: rounding,
+        }
+    }
+    pub fn precision(self: &Self) -> Precision {
+        self.precision
+    }
+    pub fn rounding(self: &Self) -> RoundingMode {
+        self.rounding
+    }
+    pub fn current() -> Self {
+        CURRENT_CONTEXT.with(|current| current.get())
+    }
+    pub fn set_current(context: Self) -> Self {
+        // the previous context is returned
+        CURRENT_CONTEXT.with(|current| current.replace(context))
+    }
+    pub fn scope<T>(self: &Self, f: impl FnOnce() -> T) -> T {
+        // f runs with self as the current context
+        let _guard: ContextGuard = ContextGuard {
+            previous: Self::set_current(*self),
+        };
+        f()
+    }
+
+    // the exact result rounded once, so every operation is correctly rounded
+    pub fn round(self: &Self, n: Float) -> Float {
+        Float::round(n, self)
+    }
+    pub fn opposite(self: &Self, n: Float) -> Float {
+        Float::round(Float::opposite(n), self)
+    }
+    pub fn abs(self: &Self, n: Float) -> Float {
+        Float::round(Float::abs(n), self)
+    }
+    pub fn add(self: &Self, a: Float, b: Float) -> Float {
+        Float::add_context(a, b, self)
+    }
+    pub fn sub(self: &Self, a: Float, b: Float) -> Float {
+        Float::sub_context(a, b, self)
+    }
+    pub fn mul(self: &Self, a: Float, b: Float) -> Float {
+        Float::round(Float::mul_exact(a, b), self)
+    }
+    pub fn div(self: &Self, a: Float, b: Float) -> Result<Float, ArithmeticError> {
+        Float::div_context(a, b, self)
+    }
+    pub fn sqrt(self: &Self, n: Float) -> Result<Float, ArithmeticError> {
+        Float::sqrt(n, self)
+    }
+    pub fn cbrt(self: &Self, n: Float) -> Result<Float, ArithmeticError> {
+        Float::cbrt(n, self)
+    }
+    pub fn nth_root(self: &Self, n: Float, k: u32) -> Result<Float, ArithmeticError> {
+        Float::nth_root(n, k, self)
+    }
+    pub fn exp(self: &Self, n: Float) -> Result<Float, ArithmeticError> {
+        Float::exp(n, self)
+    }
+    pub fn ln(self: &Self, n: Float) -> Result<Float, ArithmeticError> {
+        Float::ln(n, self)
+    }
+    pub fn log(self: &Self, n: Float, base: Float) -> Result<Float, ArithmeticError> {
+        Float::log(n, base, self)
+    }
+    pub fn log2(self: &Self, n: Float) -> Result<Float, ArithmeticError> {
+        Float::log2(n, self)
+    }
+    pub fn log10(self: &Self, n: Float) -> Result<Float, ArithmeticError> {
+        Float::log10(n, self)
+    }
+    pub fn pow(self: &Self, base: Float, exponent: Float) -> Result<Float, ArithmeticError> {
+        Float::pow(base, exponent, self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    Fixed,       // 1234.5
+    Scientific,  // 1.2345e3
+    Engineering, // 1.2345e3, 12.345e6, 123.45e-9, the exponent is a multiple of 3
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FloatFormat {
+    notation: Notation,
+    precision: Option<Precision>, // rounded to and padded up to, None keeps every digit
+    rounding: RoundingMode,
+    trim_zeros: bool,
+    grouping: Option<char>, // separator between groups of three integer digits
+}
+
+impl FloatFormat {
+    pub fn new(notation: Notation) -> Self {
+        Self {
+            notation: notation,
+            precision: None,
+            rounding: RoundingMode::HalfEven,
+            trim_zeros: false,
+            grouping: None,
+        }
+    }
+    pub fn with_precision(self: Self, precision: Precision) -> Self {
+        Self {
+            precision: Some(precision),
+            ..self
+        }
+    }
+    pub fn with_rounding(self: Self, rounding: RoundingMode) -> Self {
+        Self {
+            rounding: rounding,
+            ..self
+        }
+    }
+    pub fn with_trim_zeros(self: Self, trim_zeros: bool) -> Self {
+        Self {
+            trim_zeros: trim_zeros,
+            ..self
+        }
+    }
+    pub fn with_grouping(self: Self, separator: char) -> Self {
+        Self {
+            grouping: Some(separator),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Float {
+    point: Integer,
+    value: Integer,
+}
+
+#[allow(dead_code)]
+impl Float {
+    pub fn new(point: Integer, value: Integer) -> Self {
+        // a zero value drops its scale, 0.00 and 0e5 are both the one zero with point 0
+        let point: Integer = if value.is_zero() {
+            Integer::zero()
+        } else {
+            point
+        };
+        let result: Self = Self {
+            point: point,
+            value: value,
+        };
+        result.validate();
+        result
+    }
+    pub fn zero() -> Self {
+        Self::new(Integer::zero(), Integer::zero())
+    }
+
+    pub fn validate(self: &Self) {
+        // value * 10^-point, both parts in the canonical form of Integer, and zero has point 0
+        self.point.validate();
+        self.value.validate();
+        debug_assert!(
+            !self.value.is_zero() || self.point.is_zero(),
+            "Float: zero with a scale"
+        );
+    }
+    pub fn is_zero(self: &Self) -> bool {
+        self.value.is_zero()
+    }
+    pub fn signum(self: &Self) -> Self {
+        Self::from_integer(self.value.signum())
+    }
+    pub fn abs(n: Self) -> Self {
+        Self::new(n.point, Integer::abs(n.value))
+    }
+
+    pub fn from_f64(number: f64) -> Result<Self, TryFromFloatError> {
+        // the exact value of the IEEE-754 bits, m * 2^-e = m * 5^e / 10^e
+        if !number.is_finite() {
+            return Err(TryFromFloatError);
+        }
+        let bits: u64 = number.to_bits();
+        let biased_exponent: i64 = ((bits >> F64_MANTISSA_BITS) & 0x7ff) as i64;
+        let fraction: u64 = bits & ((1 << F64_MANTISSA_BITS) - 1);
+        let (mantissa, exponent): (u64, i64) = if biased_exponent == 0 {
+            (fraction, F64_MIN_EXPONENT)
+        } else {
+            (
+                fraction | (1 << F64_MANTISSA_BITS),
+                biased_exponent + F64_MIN_EXPONENT - 1,
+            )
+        };
+        let mut value: Integer = Integer::from(mantissa);
+        let mut point: Integer = Integer::zero();
+        if exponent >= 0 {
+            value = Integer::shl(value, exponent as usize);
+        } else {
+            let five_power: Integer =
+                Integer::pow(Integer::from(5), Integer::from(-exponent)).unwrap();
+            value = Integer::mul(value, five_power);
+            point = Integer::from(-exponent);
+        }
+        value.sign = number.is_sign_negative() && !value.is_zero();
+        let mut result: Self = Self::new(point, value);
+        result.tidy();
+        Ok(result)
+    }
+    pub fn from_f64_shortest(number: f64) -> Result<Self, TryFromFloatError> {
+        // the fewest digits that still read back as the same f64, 0.1 for 0.1
+        // std formats f64 this way already
+        if !number.is_finite() {
+            return Err(TryFromFloatError);
+        }
+        Ok(Self::parse(&format!("{:e}", number)).unwrap())
+    }
+    pub fn from_string(string: String) -> Result<Self, ParseNumberError> {
+        Self::parse(&string)
+    }
+    fn parse(string: &str) -> Result<Self, ParseNumberError> {
+        Self::parse_within(string, Some(FLOAT_MAX_POINT))
+    }
+    fn parse_within(string: &str, max_point: Option<usize>) -> Result<Self, ParseNumberError> {
+        // [+-]digits[.digits][(e|E)[+-]digits], digits may be separated by '_'
+        // or [+-](0x|0o|0b)digits for an integral value
+        // a point beyond max_point is OutOfRange, None reads any point back
+        let bytes: &[u8] = string.as_bytes();
+        let (sign, position) = parse_sign(bytes, 0);
+        if parse_radix_prefix(bytes, position).0 != DECIMAL_RADIX as u32 {
+            return Ok(Self::from_integer(Integer::parse(string)?));
+        }
+        let (integer_digits, mut position) = parse_digits(bytes, position, DECIMAL_RADIX as u32)?;
+        let mut float_digits: Vec<u8> = Vec::new();
+        // an out of range point is reported at the digits that set it
+        let mut point_position: usize = position;
+        if bytes.get(position) == Some(&b'.') {
+            point_position = position + 1;
+            (float_digits, position) = parse_digits(bytes, position + 1, DECIMAL_RADIX as u32)?;
+        }
+        if integer_digits.is_empty() && float_digits.is_empty() {
+            return Err(ParseNumberError::new(ParseNumberErrorKind::Empty, position));
+        }
+        let mut exponent: Integer = Integer::zero();
+        if matches!(bytes.get(position), Some(b'e' | b'E')) {
+            let (exponent_sign, exponent_position) = parse_sign(bytes, position + 1);
+            point_position = exponent_position;
+            let (exponent_digits, end) =
+                parse_digits(bytes, exponent_position, DECIMAL_RADIX as u32)?;
+            if exponent_digits.is_empty() {
+                return Err(ParseNumberError::new(ParseNumberErrorKind::Empty, end));
+            }
+            exponent = Integer::digits_from_radix(&exponent_digits, DECIMAL_RADIX as u32);
+            exponent.sign = exponent_sign && !exponent.is_digits_zero();
+            position = end;
+        }
+        if position < bytes.len() {
+            return Err(ParseNumberError::new(
+                ParseNumberErrorKind::InvalidDigit,
+                position,
+            ));
+        }
+        // value * 10^-point, point = (number of float digits) - exponent
+        let point: Integer = Integer::sub(Integer::from(float_digits.len()), exponent);
+        if let Some(max_point) = max_point {
+            if Integer::abs(point.clone()) > Integer::from(max_point) {
+                return Err(ParseNumberError::new(
+                    ParseNumberErrorKind::OutOfRange,
+                    point_position,
+                ));
+            }
+        }
+        let mut value: Integer = Integer::digits_from_radix(
+            &[integer_digits, float_digits].concat(),
+            DECIMAL_RADIX as u32,
+        );
+        value.sign = sign && !value.is_digits_zero();
+        Ok(Self::new(point, value))
+    }
+    pub fn from_integer(n: Integer) -> Self {
+        Self::new(Integer::zero(), n)
+    }
+    pub fn to_f64(self: Self) -> f64 {
+        // correctly rounded, ties to even, with subnormals and overflow to infinity
+        let negative: bool = self.value.sign;
+        let signed = |magnitude: f64| if negative { -magnitude } else { magnitude };
+        if self.value.is_zero() {
+            return signed(0.0);
+        }
+        // 10^(magnitude - 1) <= |n| < 10^magnitude, the point is only read as an isize within
+        // the f64 range, where it differs from the length of the value by a few hundred
+        let magnitude: Integer = self.magnitude();
+        if magnitude < Integer::from_isize(F64_MIN_DECIMAL_EXPONENT) {
+            return signed(0.0);
+        }
+        if magnitude > Integer::from_isize(F64_MAX_DECIMAL_EXPONENT + 1) {
+            return signed(f64::INFINITY);
+        }
+        // point = length - magnitude, both within the isize range here
+        let point: isize = self.value.decimal_length() as isize - Integer::to_isize(magnitude);
+        // |value| * 10^-point = numerator / denominator
+        let mut numerator: Integer = Integer::abs(self.value);
+        let mut denominator: Integer = Integer::one();
+        if point < 0 {
+            numerator.high_shift(point.unsigned_abs() as u32);
+        } else {
+            denominator = Integer::pow10(point as usize);
+        }
+        // quotient = floor(numerator * 2^shift / denominator) gets 55 or 56 bits, one guard bit at least
+        let shift: isize =
+            55 - (numerator.bit_length() as isize - denominator.bit_length() as isize);
+        if shift >= 0 {
+            numerator = Integer::shl(numerator, shift as usize);
+        } else {
+            denominator = Integer::shl(denominator, shift.unsigned_abs());
+        }
+        let (quotient, remainder) = Integer::digits_div_rem(numerator, denominator);
+        let quotient: u128 = quotient.to_u128_magnitude().unwrap();
+        // the result is mantissa * 2^unit, the unit is fixed at the subnormal one below the normal range
+        let log2: isize = 127 - quotient.leading_zeros() as isize - shift;
+        let unit: isize = max(log2 - F64_MANTISSA_BITS as isize, F64_MIN_EXPONENT as isize);
+        let dropped: u32 = (unit + shift) as u32;
+        let mut mantissa: u128 = quotient >> dropped;
+        let half: u128 = 1 << (dropped - 1);
+        let low: u128 = quotient & ((1 << dropped) - 1);
+        if low > half || (low == half && (!remainder.is_zero() || mantissa & 1 == 1)) {
+            mantissa += 1;
+        }
+        // a normal mantissa carries its hidden bit, so the exponent field comes out of the sum
+        let biased_exponent: u128 = (unit - F64_MIN_EXPONENT as isize) as u128;
+        let bits: u128 = (biased_exponent << F64_MANTISSA_BITS) + mantissa;
+        if bits >= 0x7ff << F64_MANTISSA_BITS {
+            return signed(f64::INFINITY);
+        }
+        signed(f64::from_bits(bits as u64))
+    }
+    pub fn opposite(n: Self) -> Self {
+        Self::new(n.point, Integer::opposite(n.value))
+    }
+
+    fn magnitude(self: &Self) -> Integer {
+        // m with 10^(m - 1) <= |n| < 10^m, n is not zero
+        Integer::sub(
+            Integer::from(self.value.decimal_length()),
+            self.point.clone(),
+        )
+    }
+    fn align(a: Self, b: Self) -> (Integer, Integer, Integer) {
+        // (a.value, b.value, point) shifted onto the larger of the two points
+        let delta: isize = Integer::to_isize(Integer::sub(a.point.clone(), b.point.clone()));
+        let shift: u32 =
+            u32::try_from(delta.unsigned_abs()).expect("Float points too far apart to align");
+        let mut a_value: Integer = a.value;
+        let mut b_value: Integer = b.value;
+        if delta >= 0 {
+            b_value.high_shift(shift);
+            (a_value, b_value, a.point)
+        } else {
+            a_value.high_shift(shift);
+            (a_value, b_value, b.point)
+        }
+    }
+    pub fn cmp(a: Self, b: Self) -> Ordering {
+        // the signs first, then the decimal magnitudes, the values are only aligned when both tie
+        // and then their points differ by no more than their lengths do
+        let sign = |n: &Self| -> i8 {
+            if n.value.is_digits_zero() {
+                0
+            } else if n.value.sign {
+                -1
+            } else {
+                1
+            }
+        };
+        let sign_order: Ordering = sign(&a).cmp(&sign(&b));
+        if sign_order != Ordering::Equal || a.value.is_digits_zero() {
+            return sign_order;
+        }
+        let magnitude_order: Ordering = Integer::cmp(a.magnitude(), b.magnitude());
+        if magnitude_order != Ordering::Equal {
+            return if a.value.sign {
+                magnitude_order.reverse()
+            } else {
+                magnitude_order
+            };
+        }
+        let (a_value, b_value, _) = Self::align(a, b);
+        Integer::cmp(a_value, b_value)
+    }
+
+    fn tidy(self: &mut Self) {
+        self.value.tidy();
+        if self.value.is_digits_zero() {
+            self.point = Integer::zero();
+            return;
+        }
+        // strip the trailing decimal zeros in place, nine at a time while possible
+        let mut zero_count: usize = 0;
+        while self.value.digits_rem_small(DECIMAL_CHUNK_RADIX) == 0 {
+            self.value.digits_short_div_assign(DECIMAL_CHUNK_RADIX);
+            zero_count += DECIMAL_CHUNK_WIDTH;
+        }
+        while self.value.digits_rem_small(DECIMAL_RADIX as Digit) == 0 {
+            self.value.digits_short_div_assign(DECIMAL_RADIX as Digit);
+            zero_count += 1;
+        }
+        if zero_count > 0 {
+            self.point.sub_assign(&Integer::from(zero_count));
+        }
+        self.value.tidy();
+        self.validate();
+    }
+    fn add_shift(larger: &Integer, smaller: &Integer) -> Option<u32> {
+        // the exact sum writes out every digit between the two points, None past u32::MAX
+        u32::try_from(Integer::sub(larger.clone(), smaller.clone())).ok()
+    }
+    fn add_signed_assign(self: &mut Self, rhs: &Self, negate: bool) -> Result<(), ArithmeticError> {
+        // self += rhs (or -rhs) exactly, only rhs is copied when it needs a larger point
+        // nonzero values with points more than u32::MAX apart are an Overflow and leave self as it
+        // was, a zero on either side still gives the other value back with its own point
+        let rhs_sign: bool = rhs.value.sign != negate;
+        match Integer::cmp_ref(&self.point, &rhs.point) {
+            Ordering::Less => match Self::add_shift(&rhs.point, &self.point) {
+                Some(shift) => {
+                    self.value.high_shift(shift);
+                    self.point.clone_from(&rhs.point);
+                    self.value.add_signed_assign(&rhs.value, rhs_sign);
+                }
+                None if self.value.is_digits_zero() => {
+                    self.point.clone_from(&rhs.point);
+                    self.value.clone_from(&rhs.value);
+                    self.value.sign = rhs_sign && !self.value.is_digits_zero();
+                }
+                None if rhs.value.is_digits_zero() => {}
+                None => return Err(ArithmeticError::Overflow),
+            },
+            Ordering::Greater => match Self::add_shift(&self.point, &rhs.point) {
+                Some(shift) => {
+                    let mut shifted: Integer = rhs.value.clone();
+                    shifted.high_shift(shift);
+                    self.value.add_signed_assign(&shifted, rhs_sign);
+                }
+                None if rhs.value.is_digits_zero() => {}
+                None if self.value.is_digits_zero() => {
+                    self.point.clone_from(&rhs.point);
+                    self.value.clone_from(&rhs.value);
+                    self.value.sign = rhs_sign && !self.value.is_digits_zero();
+                }
+                None => return Err(ArithmeticError::Overflow),
+            },
+            Ordering::Equal => {
+                self.value.add_signed_assign(&rhs.value, rhs_sign);
+            }
+        }
+        if self.value.is_zero() {
+            self.point = Integer::zero();
+        }
+        self.validate();
+        Ok(())
+    }
+    fn add_signed_assign_context(self: &mut Self, rhs: &Self, negate: bool, context: &Context) {
+        // self += rhs (or -rhs) rounded under the context, an operand that lies wholly below the
+        // rounding unit is first replaced with a sticky digit just under it, so the values are
+        // never shifted further apart than the precision and the longer operand need
+        let mut sticky: Option<Self> = None;
+        if !self.is_zero() && !rhs.is_zero() {
+            let rhs_is_small: bool =
+                Integer::cmp(self.magnitude(), rhs.magnitude()) != Ordering::Less;
+            let (large, small): (&Self, &Self) = if rhs_is_small {
+                (&*self, rhs)
+            } else {
+                (rhs, &*self)
+            };
+            // the sum is rounded to a multiple of 10^unit, one lower in case it loses a digit
+            let unit: Integer = match context.precision {
+                Precision::DecimalPlaces(places) => Integer::from_isize(-places),
+                Precision::SignificantDigits(digits) => {
+                    Integer::sub(large.magnitude(), Integer::from(digits.get() + 1))
+                }
+            };
+            let lowest: Integer = min(unit, Integer::opposite(large.point.clone()));
+            if Integer::cmp(
+                small.magnitude(),
+                Integer::sub(lowest.clone(), Integer::one()),
+            ) != Ordering::Greater
+            {
+                // large and every rounding boundary are multiples of 10^(lowest - 1), so any
+                // small value of the same sign rounds alike and this one needs no long shift
+                let value: Integer = if small.value.sign {
+                    Integer::from(-1)
+                } else {
+                    Integer::one()
+                };
+                let replacement: Self = Self::new(Integer::sub(Integer::from(2), lowest), value);
+                if rhs_is_small {
+                    sticky = Some(replacement);
+                } else {
+                    *self = replacement;
+                }
+            }
+        }
+        let rhs: &Self = sticky.as_ref().unwrap_or(rhs);
+        // the shift is bounded now, so the exact sum always fits
+        self.add_signed_assign(rhs, negate)
+            .expect("Float sum within the precision overflowed");
+        *self = Self::round(std::mem::replace(self, Self::zero()), context);
+    }
+    pub fn add_assign(self: &mut Self, rhs: &Self) {
+        // self += rhs rounded under the current context
+        self.add_signed_assign_context(rhs, false, &Context::current());
+    }
+    pub fn sub_assign(self: &mut Self, rhs: &Self) {
+        self.add_signed_assign_context(rhs, true, &Context::current());
+    }
+    pub fn add_assign_context(self: &mut Self, rhs: &Self, context: &Context) {
+        self.add_signed_assign_context(rhs, false, context);
+    }
+    pub fn sub_assign_context(self: &mut Self, rhs: &Self, context: &Context) {
+        self.add_signed_assign_context(rhs, true, context);
+    }
+    pub fn add(a: Self, b: Self) -> Self {
+        // a + b rounded under the current context
+        let mut result: Self = a;
+        result.add_assign(&b);
+        result
+    }
+    pub fn sub(a: Self, b: Self) -> Self {
+        let mut result: Self = a;
+        result.sub_assign(&b);
+        result
+    }
+    pub fn add_exact(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        // a + b with every digit, Overflow when that takes more than u32::MAX appended zeros
+        let mut result: Self = a;
+        result.add_signed_assign(&b, false)?;
+        Ok(result)
+    }
+    pub fn sub_exact(a: Self, b: Self) -> Result<Self, ArithmeticError> {
+        let mut result: Self = a;
+        result.add_signed_assign(&b, true)?;
+        Ok(result)
+    }
+    pub fn add_context(a: Self, b: Self, context: &Context) -> Self {
+        // a + b rounded under the context
+        let mut result: Self = a;
+        result.add_assign_context(&b, context);
+        result
+    }
+    pub fn sub_context(a: Self, b: Self, context: &Context) -> Self {
+        // a - b rounded under the context
+        let mut result: Self = a;
+        result.sub_assign_context(&b, context);
+        result
+    }
+
+    fn point_isize(point: &Integer) -> Result<isize, ArithmeticError> {
+        // parsed points are far inside the range, only a hand built point or a huge scale leaves it
+        isize::try_from(point).map_err(|_| ArithmeticError::Overflow)
+    }
+    fn shift_count(count: usize) -> Result<u32, ArithmeticError> {
+        // a decimal shift of the value, checked rather than truncated
+        u32::try_from(count).map_err(|_| ArithmeticError::Overflow)
+    }
+    fn high_shift(self: &mut Self, times: u32) {
+        self.point = Integer::sub(self.point.clone(), Integer::from_isize(times as isize));
+    }
+    fn low_shift(self: &mut Self, times: u32) {
+        self.point = Integer::add(self.point.clone(), Integer::from_isize(times as isize));
+    }
+
+    pub fn mul(a: Self, b: Self) -> Self {
+        // a * b rounded under the current context
+        Self::round(Self::mul_exact(a, b), &Context::current())
+    }
+    pub fn mul_exact(a: Self, b: Self) -> Self {
+        // a * b with every digit, the points add up
+        Self::new(
+            Integer::add(a.point, b.point),
+            Integer::mul(a.value, b.value),
+        )
+    }
+    fn round_inexact(
+        magnitude: &mut Integer,
+        half: impl FnOnce(&Integer) -> Ordering,
+        negative: bool,
+        rounding: RoundingMode,
+    ) {
+        // magnitude plus a fraction strictly between 0 and 1 to an integer, negative is the
+        // result sign, half compares that fraction with 1/2 and is only asked when it matters
+        let round_up: bool = match rounding {
+            RoundingMode::Truncate => false,
+            RoundingMode::AwayFromZero => true,
+            RoundingMode::Floor => negative,
+            RoundingMode::Ceiling => !negative,
+            RoundingMode::HalfEven | RoundingMode::HalfUp => match half(magnitude) {
+                Ordering::Less => false,
+                Ordering::Equal => rounding == RoundingMode::HalfUp || !magnitude.is_even(),
+                Ordering::Greater => true,
+            },
+        };
+        if round_up {
+            magnitude.add_signed_assign(&Integer::one(), false);
+        }
+    }
+    fn round_quotient(
+        quotient: &mut Integer,
+        remainder: &Integer,
+        divisor: &Integer,
+        negative: bool,
+        rounding: RoundingMode,
+    ) {
+        // quotient + remainder / divisor to an integer, magnitudes only
+        if remainder.is_zero() {
+            return;
+        }
+        let half = |_: &Integer| {
+            let mut twice: Integer = remainder.clone();
+            twice.digits_mul_add_small(2, 0);
+            Integer::digits_cmp(&twice, divisor)
+        };
+        Self::round_inexact(quotient, half, negative, rounding);
+    }
+    fn drop_digits(n: Self, count: &Integer, rounding: RoundingMode) -> Self {
+        // removes the lowest count decimal digits of the value, rounding what they held
+        let negative: bool = n.value.sign;
+        let mut quotient: Integer = Integer::zero();
+        match usize::try_from(count) {
+            Ok(count) if n.value.decimal_length() + 2 > count => {
+                let divisor: Integer = Integer::pow10(count);
+                let remainder: Integer;
+                (quotient, remainder) = Integer::digits_div_rem(n.value, divisor.clone());
+                Self::round_quotient(&mut quotient, &remainder, &divisor, negative, rounding);
+            }
+            _ => {
+                // all digits are dropped and they held less than half a unit, 10^count is never built
+                if !n.value.is_zero() {
+                    Self::round_inexact(&mut quotient, |_| Ordering::Less, negative, rounding);
+                }
+            }
+        }
+        quotient.sign = negative && !quotient.is_zero();
+        Self::new(Integer::sub(n.point, count.clone()), quotient)
+    }
+    fn round_places(n: Self, places: isize, rounding: RoundingMode) -> Result<Self, Self> {
+        // round_to, or n itself back when it would take more than u32::MAX appended zeros
+        let drop: Integer = Integer::sub(n.point.clone(), Integer::from(places));
+        if !drop.sign && !drop.is_zero() {
+            return Ok(Self::drop_digits(n, &drop, rounding));
+        }
+        if n.value.is_zero() {
+            return Ok(Self::zero());
+        }
+        match u32::try_from(Integer::abs(drop)) {
+            Ok(zeros) => {
+                let mut value: Integer = n.value;
+                value.high_shift(zeros);
+                Ok(Self::new(Integer::from(places), value))
+            }
+            Err(_) => Err(n),
+        }
+    }
+    pub fn round_to(
+        n: Self,
+        places: isize,
+        rounding: RoundingMode,
+    ) -> Result<Self, ArithmeticError> {
+        // exactly places digits after the point, zeros are appended when n has fewer,
+        // a zero result is the one zero and has no digits after the point
+        Self::round_places(n, places, rounding).map_err(|_| ArithmeticError::Overflow)
+    }
+    pub fn round_sig(n: Self, digits: NonZeroUsize, rounding: RoundingMode) -> Self {
+        // at most digits significant digits, shorter values are left as they are
+        let digits: usize = digits.get();
+        let length: usize = n.value.decimal_length();
+        if length <= digits {
+            return n;
+        }
+        let mut result: Self = Self::drop_digits(n, &Integer::from(length - digits), rounding);
+        if result.value.decimal_length() > digits {
+            // rounding carried into a new digit, 99.9.. -> 100, the dropped digit is a zero
+            result = Self::drop_digits(result, &Integer::one(), RoundingMode::Truncate);
+        }
+        result
+    }
+    pub fn trunc(n: Self) -> Self {
+        // a point at or below zero already makes n an integer, no zeros are appended
+        if n.point.sign || n.point.is_zero() {
+            return n;
+        }
+        let count: Integer = n.point.clone();
+        Self::drop_digits(n, &count, RoundingMode::Truncate)
+    }
+    pub fn round(n: Self, context: &Context) -> Self {
+        // the value is exact either way, so a scale too long to write out is left off
+        match context.precision {
+            Precision::SignificantDigits(digits) => Self::round_sig(n, digits, context.rounding),
+            Precision::DecimalPlaces(places) => {
+                Self::round_places(n, places, context.rounding).unwrap_or_else(|n| n)
+            }
+        }
+    }
+
+    pub fn div(a: Self, b: Self, precision: Precision) -> Result<Self, ArithmeticError> {
+        // a / b correctly rounded to the requested precision, ties to even
+        Self::div_context(a, b, &Context::new(precision, RoundingMode::HalfEven))
+    }
+    pub fn div_context(a: Self, b: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        // a / b correctly rounded under the context
+        if b.is_zero() {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        let sign: bool = a.value.sign != b.value.sign;
+        let mut dividend: Integer = Integer::abs(a.value);
+        let mut divisor: Integer = Integer::abs(b.value);
+        // result point = shift + a.point - b.point, the quotient is |a.value| * 10^shift / |b.value|
+        let shift: Integer = match context.precision {
+            Precision::DecimalPlaces(places) => Integer::add(
+                Integer::from_isize(places),
+                Integer::sub(b.point.clone(), a.point.clone()),
+            ),
+            Precision::SignificantDigits(digits) => {
+                if dividend.is_zero() {
+                    return Ok(Self::zero());
+                }
+                // leaves the quotient with digits or digits + 1 digits
+                Integer::from_isize(
+                    digits.get() as isize - dividend.decimal_length() as isize
+                        + divisor.decimal_length() as isize,
+                )
+            }
+        };
+        let mut point: Integer = Integer::add(shift.clone(), Integer::sub(a.point, b.point));
+        if shift.sign
+            && Integer::from(dividend.decimal_length() + 2)
+                <= Integer::sub(Integer::from(divisor.decimal_length()), shift.clone())
+        {
+            // |a / b| is far below one unit of the result, so it is 0 and a fraction under 1/2
+            let mut quotient: Integer = Integer::zero();
+            if !dividend.is_zero() {
+                Self::round_inexact(&mut quotient, |_| Ordering::Less, sign, context.rounding);
+            }
+            quotient.sign = sign && !quotient.is_zero();
+            return Ok(Self::new(point, quotient));
+        }
+        // only a shift that is written out here can fail, the quotient would have as many digits
+        let shift: isize = Self::point_isize(&shift)?;
+        if shift >= 0 {
+            dividend.high_shift(Self::shift_count(shift as usize)?);
+        } else {
+            divisor.high_shift(Self::shift_count(shift.unsigned_abs())?);
+        }
+        let (mut quotient, mut remainder) = Integer::div_rem(dividend, divisor.clone())?;
+        if let Precision::SignificantDigits(digits) = context.precision {
+            if quotient.decimal_length() > digits.get() {
+                // fold the extra digit into the remainder: q = 10h + l, so r' = l * divisor + r
+                let low: Digit = quotient.digits_short_div_assign(DECIMAL_RADIX as Digit);
+                let mut low_part: Integer = divisor.clone();
+                low_part.digits_mul_add_small(low, 0);
+                remainder.add_signed_assign(&low_part, false);
+                divisor.digits_mul_add_small(DECIMAL_RADIX as Digit, 0);
+                point.sub_assign(&Integer::one());
+            }
+        }
+        Self::round_quotient(&mut quotient, &remainder, &divisor, sign, context.rounding);
+        if let Precision::SignificantDigits(digits) = context.precision {
+            if quotient.decimal_length() > digits.get() {
+                // rounding carried into a new digit, the dropped digit is a zero
+                quotient.digits_short_div_assign(DECIMAL_RADIX as Digit);
+                point.sub_assign(&Integer::one());
+            }
+        }
+        quotient.sign = sign && !quotient.is_zero();
+        Ok(Self::new(point, quotient))
+    }
+
+    pub fn nth_root(n: Self, k: u32, context: &Context) -> Result<Self, ArithmeticError> {
+        // the k-th root correctly rounded under the context, an odd root of a negative n is negative
+        if k == 0 {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        let negative: bool = n.value.sign;
+        if negative && k.is_multiple_of(2) {
+            return Err(ArithmeticError::NegativeRoot);
+        }
+        let mut value: Integer = Integer::abs(n.value);
+        let target: Integer = match context.precision {
+            Precision::DecimalPlaces(places) => Integer::from_isize(places),
+            Precision::SignificantDigits(digits) => {
+                if value.is_zero() {
+                    return Ok(Self::zero());
+                }
+                // floor(log10 |n|) = length - 1 - point, so the floor of the root gets digits digits
+                let log10: Integer =
+                    Integer::sub(Integer::from(value.decimal_length() - 1), n.point.clone());
+                Integer::sub(
+                    Integer::from(digits.get() - 1),
+                    Integer::div_floor(log10, Integer::from(k)).unwrap(),
+                )
+            }
+        };
+        // root * 10^target = (value * 10^(k * target - point))^(1/k), a negative exponent leaves
+        // the fraction value / 10^scale, whose floor has the same integer root
+        let exponent: Integer =
+            Integer::sub(Integer::mul(Integer::from(k), target.clone()), n.point);
+        if exponent.sign
+            && Integer::from(value.decimal_length() + k as usize) <= Integer::abs(exponent.clone())
+        {
+            // |n| * 10^(k * target) < 10^-k, so the root is far below one unit of the result,
+            // it is 0 and a fraction under 1/2
+            let mut root: Integer = Integer::zero();
+            if !value.is_zero() {
+                Self::round_inexact(&mut root, |_| Ordering::Less, negative, context.rounding);
+            }
+            root.sign = negative && !root.is_zero();
+            return Ok(Self::new(target, root));
+        }
+        // only an exponent that is written out here can fail, the root would have as many digits
+        let exponent: isize = Self::point_isize(&exponent)?;
+        let mut scale: usize = 0;
+        if exponent >= 0 {
+            value.high_shift(Self::shift_count(exponent as usize)?);
+        } else {
+            scale = exponent.unsigned_abs();
+        }
+        let scale_count: u32 = Self::shift_count(scale)?;
+        let (radicand, fraction) = Integer::digits_div_rem(value.clone(), Integer::pow10(scale));
+        let (mut root, remainder) = Integer::nth_root(radicand, k)?;
+        if !remainder.is_zero() || !fraction.is_zero() {
+            // root + 1/2 against the exact root: value * 2^k against (2 root + 1)^k * 10^scale
+            let half = |root: &Integer| {
+                let mut bound: Integer = Integer::pow_u32(
+                    Integer::add(Integer::shl(root.clone(), 1), Integer::one()),
+                    k,
+                );
+                bound.high_shift(scale_count);
+                Integer::digits_cmp(&Integer::shl(value, k as usize), &bound)
+            };
+            Self::round_inexact(&mut root, half, negative, context.rounding);
+        }
+        let mut point: Integer = target;
+        if let Precision::SignificantDigits(digits) = context.precision {
+            if root.decimal_length() > digits.get() {
+                // rounding carried into a new digit, the dropped digit is a zero
+                root.digits_short_div_assign(DECIMAL_RADIX as Digit);
+                point.sub_assign(&Integer::one());
+            }
+        }
+        root.sign = negative && !root.is_zero();
+        Ok(Self::new(point, root))
+    }
+    pub fn sqrt(n: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        Self::nth_root(n, 2, context)
+    }
+    pub fn cbrt(n: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        Self::nth_root(n, 3, context)
+    }
+
+    fn group_digits(digits: &str, separator: char) -> String {
+        // 1234567 -> 1,234,567
+        let mut grouped: String = String::with_capacity(digits.len() * 4 / 3 + 1);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+    pub fn format(self: &Self, format: &FloatFormat) -> String {
+        let mut n: Self = self.clone();
+        if let Some(precision) = format.precision {
+            n = Self::round(n, &Context::new(precision, format.rounding));
+            if let Precision::SignificantDigits(digits) = precision {
+                // 1.5 to four significant digits is 1.500 and 0 is 0.000
+                if n.is_zero() {
+                    n.point = Integer::from(digits.get() - 1);
+                } else {
+                    let missing: usize = digits.get().saturating_sub(n.value.decimal_length());
+                    n.value.high_shift(missing as u32);
+                    n.point.add_assign(&Integer::from(missing));
+                }
+            }
+        }
+        let mut digits: String = Integer::abs(n.value.clone()).to_string();
+        // at most FORMAT_MAX_ZEROS zeros are written out next to the digits, fixed notation
+        // falls back to scientific beyond them
+        let point: Option<isize> = isize::try_from(&n.point).ok().filter(|point| {
+            let zeros: usize = if *point <= 0 {
+                // a zero above the units is written as a single 0
+                if n.value.is_zero() {
+                    0
+                } else {
+                    point.unsigned_abs()
+                }
+            } else {
+                (*point as usize + 1).saturating_sub(digits.len())
+            };
+            zeros <= FORMAT_MAX_ZEROS
+        });
+        let (mut integer_part, mut fraction_part, exponent): (String, String, Option<Integer>) =
+            match (format.notation, point) {
+                (Notation::Fixed, Some(point)) => {
+                    if point <= 0 {
+                        if !n.value.is_zero() {
+                            digits.push_str(&"0".repeat(point.unsigned_abs()));
+                        }
+                        (digits, String::new(), None)
+                    } else {
+                        let width: usize = point as usize + 1;
+                        if digits.len() < width {
+                            digits.insert_str(0, &"0".repeat(width - digits.len()));
+                        }
+                        let fraction_part: String = digits.split_off(digits.len() - point as usize);
+                        (digits, fraction_part, None)
+                    }
+                }
+                _ => {
+                    // digits = d.ddd * 10^(length - 1), a zero keeps its scale as fraction digits
+                    // or, out of range, as the exponent
+                    let mut exponent: Integer = Integer::zero();
+                    if !n.value.is_zero() {
+                        exponent = Integer::sub(Integer::from(digits.len() - 1), n.point.clone());
+                    } else if let Some(point) = point {
+                        digits.push_str(&"0".repeat(max(point, 0) as usize));
+                    } else {
+                        exponent = Integer::opposite(n.point.clone());
+                    }
+                    let integer_width: usize = match format.notation {
+                        Notation::Engineering => {
+                            let remainder: Integer =
+                                Integer::rem_euclid(exponent.clone(), Integer::from(3)).unwrap();
+                            usize::try_from(remainder).unwrap() + 1
+                        }
+                        _ => 1,
+                    };
+                    exponent.sub_assign(&Integer::from(integer_width - 1));
+                    if digits.len() < integer_width {
+                        digits.push_str(&"0".repeat(integer_width - digits.len()));
+                    }
+                    let fraction_part: String = digits.split_off(integer_width);
+                    (digits, fraction_part, Some(exponent))
+                }
+            };
+        if format.trim_zeros {
+            fraction_part.truncate(fraction_part.trim_end_matches('0').len());
+        }
+        if let Some(separator) = format.grouping {
+            integer_part = Self::group_digits(&integer_part, separator);
+        }
+        let mut result: String = String::new();
+        if n.value.sign {
+            result.push('-');
+        }
+        result.push_str(&integer_part);
+        if !fraction_part.is_empty() {
+            result.push('.');
+            result.push_str(&fraction_part);
+        }
+        if let Some(exponent) = exponent {
+            result.push_str(&format!("e{}", exponent));
+        }
+        result
+    }
+
+    fn fixed_from(n: &Self, bits: usize) -> Result<Integer, ArithmeticError> {
+        // floor(n * 2^bits)
+        let point: isize = Self::point_isize(&n.point)?;
+        let mut shifted: Integer = Integer::shl(n.value.clone(), bits);
+        if point >= 0 {
+            return Integer::div_floor(shifted, Integer::pow10(point as usize));
+        }
+        shifted.high_shift(Self::shift_count(point.unsigned_abs())?);
+        Ok(shifted)
+    }
+    fn fixed_atanh_inverse(n: u32, bits: usize) -> Integer {
+        // atanh(1/n) * 2^bits = sum of 2^bits / ((2k + 1) n^(2k + 1)), two ulps of error per term
+        let square: Integer = Integer::from(n as u64 * n as u64);
+        let mut term: Integer =
+            Integer::div(Integer::shl(Integer::one(), bits), Integer::from(n)).unwrap();
+        let mut sum: Integer = term.clone();
+        let mut k: u64 = 1;
+        loop {
+            term = Integer::div(term, square.clone()).unwrap();
+            if term.is_zero() {
+                return sum;
+            }
+            sum.add_assign(&Integer::div(term.clone(), Integer::from(2 * k + 1)).unwrap());
+            k += 1;
+        }
+    }
+    fn fixed_guard(bits: usize) -> usize {
+        // enough extra bits to swallow one ulp of error from each of up to bits steps
+        Integer::from(bits).bit_length() + 4
+    }
+    fn fixed_ln2(bits: usize) -> Integer {
+        // ln 2 = 2 atanh(1/3), within two ulps
+        let guard: usize = Self::fixed_guard(bits);
+        let ln2: Integer = Integer::shl(Self::fixed_atanh_inverse(3, bits + guard), 1);
+        Integer::shr(ln2, guard)
+    }
+    fn fixed_ln10(bits: usize) -> Integer {
+        // ln 10 = 3 ln 2 + ln 1.25 = 6 atanh(1/3) + 2 atanh(1/9), within two ulps
+        let guard: usize = Self::fixed_guard(bits);
+        let mut ln10: Integer =
+            Integer::mul(Self::fixed_atanh_inverse(3, bits + guard), Integer::from(3));
+        ln10.add_assign(&Self::fixed_atanh_inverse(9, bits + guard));
+        Integer::shr(Integer::shl(ln10, 1), guard)
+    }
+    fn fixed_mul_constant(k: &Integer, constant: fn(usize) -> Integer, bits: usize) -> Integer {
+        // k * constant, within two ulps however large k is
+        let extra: usize = k.bit_length() + 2;
+        Integer::shr(Integer::mul(k.clone(), constant(bits + extra)), extra)
+    }
+    fn fixed_exp(z: &Integer, bits: usize) -> (Integer, Integer) {
+        // (mantissa, k) with exp(z / 2^bits) = mantissa / 2^bits * 10^k, mantissa is about
+        // [2^bits, 10 * 2^bits) and within 4 ulps of it
+        // exp(z) = exp(r / 2^halvings)^(2^halvings) * 10^k with r = z - k ln 10 in [0, ln 10)
+        let halvings: usize =
+            Integer::isqrt(Integer::from(bits)).unwrap().0.to_isize() as usize / 2 + 4;
+        let working: usize = bits + halvings + 2 * Self::fixed_guard(bits) + 8;
+        let z: Integer = Integer::shl(z.clone(), working - bits);
+        let k: Integer = Integer::div_floor(z.clone(), Self::fixed_ln10(working)).unwrap();
+        let r: Integer = Integer::sub(z, Self::fixed_mul_constant(&k, Self::fixed_ln10, working));
+        let r: Integer = Integer::shr(r, halvings);
+        let one: Integer = Integer::shl(Integer::one(), working);
+        let mut sum: Integer = one.clone();
+        let mut term: Integer = one;
+        let mut n: u64 = 1;
+        loop {
+            term = Integer::div(
+                Integer::shr(Integer::mul(term, r.clone()), working),
+                Integer::from(n),
+            )
+            .unwrap();
+            if term.is_zero() {
+                break;
+            }
+            sum.add_assign(&term);
+            n += 1;
+        }
+        for _ in 0..halvings {
+            sum = Integer::shr(Integer::mul(sum.clone(), sum), working);
+        }
+        (Integer::shr(sum, working - bits), k)
+    }
+    fn fixed_ln(n: &Self, bits: usize) -> Integer {
+        // ln(n) * 2^bits within 4 ulps, n > 0
+        // n = f * 2^b * 10^-point with f in [1, 2), and with g = f^(1 / 2^roots),
+        // ln f = 2^roots * 2 atanh((g - 1) / (g + 1))
+        let point: Integer = n.point.clone();
+        let b: Integer = Integer::from(n.value.bit_length() - 1);
+        let roots: usize = Integer::isqrt(Integer::from(bits / 64))
+            .unwrap()
+            .0
+            .to_isize() as usize
+            + 2;
+        let working: usize = bits + roots + 2 * Self::fixed_guard(bits) + 8;
+        let mut f: Integer = Integer::abs(n.value.clone());
+        let b_size: usize = Integer::to_isize(b.clone()) as usize;
+        f = if b_size <= working {
+            Integer::shl(f, working - b_size)
+        } else {
+            Integer::shr(f, b_size - working)
+        };
+        for _ in 0..roots {
+            f = Integer::isqrt(Integer::shl(f, working)).unwrap().0;
+        }
+        let one: Integer = Integer::shl(Integer::one(), working);
+        let t: Integer = Integer::div(
+            Integer::shl(Integer::sub(f.clone(), one.clone()), working),
+            Integer::add(f, one),
+        )
+        .unwrap();
+        let t_square: Integer = Integer::shr(Integer::mul(t.clone(), t.clone()), working);
+        let mut sum: Integer = t.clone();
+        let mut term: Integer = t;
+        let mut k: u64 = 1;
+        loop {
+            term = Integer::shr(Integer::mul(term, t_square.clone()), working);
+            if term.is_zero() {
+                break;
+            }
+            sum.add_assign(&Integer::div(term.clone(), Integer::from(2 * k + 1)).unwrap());
+            k += 1;
+        }
+        let mut ln: Integer = Integer::shl(sum, roots + 1);
+        ln.add_assign(&Self::fixed_mul_constant(&b, Self::fixed_ln2, working));
+        ln.sub_assign(&Self::fixed_mul_constant(&point, Self::fixed_ln10, working));
+        Integer::shr(ln, working - bits)
+    }
+    fn rational_parts(n: &Self) -> Result<(Integer, Integer), ArithmeticError> {
+        // |n| = numerator / denominator in lowest terms
+        let point: isize = Self::point_isize(&n.point)?;
+        let mut numerator: Integer = Integer::abs(n.value.clone());
+        if point <= 0 {
+            numerator.high_shift(Self::shift_count(point.unsigned_abs())?);
+            return Ok((numerator, Integer::one()));
+        }
+        let denominator: Integer = Integer::pow10(point as usize);
+        let gcd: Integer = Integer::gcd(numerator.clone(), denominator.clone());
+        Ok((
+            Integer::div(numerator, gcd.clone()).unwrap(),
+            Integer::div(denominator, gcd).unwrap(),
+        ))
+    }
+    fn log2_estimate(n: &Integer) -> f64 {
+        // log2 |n| from its top 64 bits, n != 0
+        let shift: usize = n.bit_length().saturating_sub(64);
+        let top: u64 = u64::try_from(Integer::abs(Integer::shr(n.clone(), shift))).unwrap();
+        (top as f64).log2() + shift as f64
+    }
+    fn ziv_round(
+        context: &Context,
+        attempt: impl Fn(usize) -> Result<Option<Vec<(Self, Self)>>, ArithmeticError>,
+    ) -> Result<Self, ArithmeticError> {
+        // attempt(bits) encloses the result between the quotients it returns, the bits are doubled
+        // until every corner rounds alike, which then is the correctly rounded result, a result
+        // that is still undecided after ZIV_MAX_DOUBLINGS doublings is reported as Overflow
+        let digits: isize = match context.precision {
+            Precision::SignificantDigits(digits) => digits.get() as isize,
+            Precision::DecimalPlaces(places) => places,
+        };
+        let mut bits: usize = (max(digits, 0) as usize)
+            .checked_mul(3322)
+            .ok_or(ArithmeticError::Overflow)?
+            / 1000
+            + 32;
+        for _ in 0..=ZIV_MAX_DOUBLINGS {
+            if let Some(corners) = attempt(bits)? {
+                let rounded: Vec<Self> = corners
+                    .into_iter()
+                    .map(|(numerator, denominator)| {
+                        Self::div_context(numerator, denominator, context)
+                    })
+                    .collect::<Result<Vec<Self>, ArithmeticError>>()?;
+                if rounded
+                    .iter()
+                    .all(|n| n.point == rounded[0].point && n.value == rounded[0].value)
+                {
+                    return Ok(rounded.into_iter().next().unwrap());
+                }
+            }
+            bits = bits.checked_mul(2).ok_or(ArithmeticError::Overflow)?;
+        }
+        Err(ArithmeticError::Overflow)
+    }
+    fn round_next_to(
+        anchor: &Self,
+        error: &Integer,
+        above: bool,
+        context: &Context,
+    ) -> Option<Self> {
+        // a value within 10^error of anchor on the given side, but not anchor itself, rounded under
+        // the context, when that is close enough for every such value to round alike, anchor != 0
+        // rounding only changes at multiples of 10^-grid near anchor, the half units and the finer
+        // units just below a power of ten included
+        let grid: Integer = match context.precision {
+            Precision::SignificantDigits(digits) => {
+                Integer::sub(Integer::from(digits.get() + 2), anchor.magnitude())
+            }
+            Precision::DecimalPlaces(places) => {
+                Integer::add(Integer::from_isize(places), Integer::one())
+            }
+        };
+        let gap: Integer = max(anchor.point.clone(), grid);
+        if Integer::add(error.clone(), gap.clone()) > Integer::zero() {
+            return None;
+        }
+        // no multiple of 10^-gap lies between anchor and the value, so anchor +- 10^-(gap + 1)
+        // rounds the same way
+        let step: Self = Self::new(
+            Integer::add(gap, Integer::one()),
+            Integer::from(if above { 1 } else { -1 }),
+        );
+        Some(Self::round(
+            Self::add_exact(anchor.clone(), step).ok()?,
+            context,
+        ))
+    }
+    fn exp_corners(
+        mantissa: Integer,
+        error: Integer,
+        k: Integer,
+        bits: usize,
+        negative: bool,
+    ) -> Vec<(Self, Self)> {
+        // +-(mantissa +- error) / 2^bits * 10^k
+        let denominator: Self = Self::from_integer(Integer::shl(Integer::one(), bits));
+        [
+            Integer::sub(mantissa.clone(), error.clone()),
+            Integer::add(mantissa, error),
+        ]
+        .into_iter()
+        .map(|value| {
+            let value: Integer = if negative {
+                Integer::opposite(value)
+            } else {
+                value
+            };
+            (
+                Self::new(Integer::opposite(k.clone()), value),
+                denominator.clone(),
+            )
+        })
+        .collect()
+    }
+    pub fn exp(n: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        // e^n correctly rounded under the context
+        if n.is_zero() {
+            return Ok(Self::round(Self::from(1), context));
+        }
+        let magnitude: Integer = n.magnitude();
+        if magnitude.sign {
+            // e^n - 1 has the sign of n and |e^n - 1| < 2 |n| < 10^(magnitude + 1) for |n| < 1/10,
+            // so a tiny n leaves 1 rounded toward its side
+            let error: Integer = Integer::add(magnitude, Integer::one());
+            if let Some(result) =
+                Self::round_next_to(&Self::from(1), &error, !n.value.sign, context)
+            {
+                return Ok(result);
+            }
+        }
+        Self::ziv_round(context, |bits| {
+            let (mantissa, k) = Self::fixed_exp(&Self::fixed_from(&n, bits)?, bits);
+            // 4 ulps from exp itself and 11 from the ulp lost in n
+            Ok(Some(Self::exp_corners(
+                mantissa,
+                Integer::from(15),
+                k,
+                bits,
+                false,
+            )))
+        })
+    }
+    pub fn ln(n: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        // natural logarithm correctly rounded under the context
+        if n.value.sign || n.is_zero() {
+            return Err(ArithmeticError::NonPositiveLogarithm);
+        }
+        if n == Self::from(1) {
+            return Ok(Self::round(Self::zero(), context));
+        }
+        let magnitude: Integer = n.magnitude();
+        if magnitude == Integer::zero() || magnitude == Integer::one() {
+            // ln(1 + e) lies between e - e^2 and e for 0 < |e| < 1/10, |e^2| < 10^(2 magnitude),
+            // so a tiny e leaves e rounded from just below it
+            let epsilon: Self = Self::sub_exact(n.clone(), Self::from(1))?;
+            let magnitude: Integer = epsilon.magnitude();
+            if magnitude.sign {
+                let error: Integer = Integer::mul(magnitude, Integer::from(2));
+                if let Some(result) = Self::round_next_to(&epsilon, &error, false, context) {
+                    return Ok(result);
+                }
+            }
+        }
+        Self::ziv_round(context, |bits| {
+            Ok(Some(Self::exp_corners(
+                Self::fixed_ln(&n, bits),
+                Integer::from(4),
+                Integer::zero(),
+                bits,
+                false,
+            )))
+        })
+    }
+    fn exact_log(n: &Self, base: &Self) -> Option<(Integer, Integer)> {
+        // (j, m) with log_base(n) = j / m when that is rational, n and base positive and base != 1
+        // base = c^m for the c that is no perfect power itself, then a rational log needs n = c^j
+        let (n_numerator, n_denominator) = Self::rational_parts(n).ok()?;
+        let (base_numerator, base_denominator) = Self::rational_parts(base).ok()?;
+        let primitive = |n: Integer| match Integer::perfect_power(n.clone()) {
+            Some((root, exp)) => (root, exp),
+            None if n.is_one() => (n, 0),
+            None => (n, 1),
+        };
+        let (numerator_root, numerator_exp) = primitive(base_numerator);
+        let (denominator_root, denominator_exp) = primitive(base_denominator);
+        let mut m: u32 = numerator_exp;
+        let mut rest: u32 = denominator_exp;
+        while rest != 0 {
+            (m, rest) = (rest, m % rest);
+        }
+        let c_numerator: Integer = Integer::pow_u32(numerator_root, numerator_exp / m);
+        let c_denominator: Integer = Integer::pow_u32(denominator_root, denominator_exp / m);
+        let estimate: f64 = (Self::log2_estimate(&n_numerator)
+            - Self::log2_estimate(&n_denominator))
+            / (Self::log2_estimate(&c_numerator) - Self::log2_estimate(&c_denominator));
+        let j: f64 = estimate.round();
+        // c^j has at least |j| bits on one side
+        if !j.is_finite()
+            || j.abs() > (n_numerator.bit_length() + n_denominator.bit_length()) as f64
+        {
+            return None;
+        }
+        let j: i64 = j as i64;
+        let (top, bottom) = if j >= 0 {
+            (c_numerator, c_denominator)
+        } else {
+            (c_denominator, c_numerator)
+        };
+        let j_size: u32 = j.unsigned_abs() as u32;
+        if Integer::pow_u32(top, j_size) == n_numerator
+            && Integer::pow_u32(bottom, j_size) == n_denominator
+        {
+            return Some((Integer::from(j), Integer::from(m)));
+        }
+        None
+    }
+    pub fn log(n: Self, base: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        // logarithm of n to the given base correctly rounded under the context
+        if n.value.sign || n.is_zero() || base.value.sign || base.is_zero() {
+            return Err(ArithmeticError::NonPositiveLogarithm);
+        }
+        if base == Self::from(1) {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        if let Some((j, m)) = Self::exact_log(&n, &base) {
+            return Self::div_context(Self::from_integer(j), Self::from_integer(m), context);
+        }
+        Self::ziv_round(context, |bits| {
+            let n_ln: Integer = Self::fixed_ln(&n, bits);
+            let base_ln: Integer = Self::fixed_ln(&base, bits);
+            let error: Integer = Integer::from(4);
+            let low: Integer = Integer::sub(base_ln.clone(), error.clone());
+            let high: Integer = Integer::add(base_ln, error.clone());
+            if low.signum() != high.signum() {
+                // ln base is too close to 0 to tell its sign yet
+                return Ok(None);
+            }
+            let mut corners: Vec<(Self, Self)> = Vec::new();
+            for numerator in [
+                Integer::sub(n_ln.clone(), error.clone()),
+                Integer::add(n_ln.clone(), error.clone()),
+            ] {
+                for denominator in [&low, &high] {
+                    corners.push((
+                        Self::from_integer(numerator.clone()),
+                        Self::from_integer(denominator.clone()),
+                    ));
+                }
+            }
+            Ok(Some(corners))
+        })
+    }
+    pub fn log2(n: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        Self::log(n, Self::from(2), context)
+    }
+    pub fn log10(n: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        Self::log(n, Self::from(10), context)
+    }
+    fn exact_pow(
+        base: &Self,
+        exponent: &Self,
+        negative: bool,
+        context: &Context,
+    ) -> Option<(Self, Self)> {
+        // base^exponent as a quotient when it is rational and not too large to write out
+        // with exponent = p / q in lowest terms, that needs both parts of |base| to be q-th powers
+        let (mut p, q) = Self::rational_parts(exponent).ok()?;
+        p.sign = exponent.value.sign;
+        let (base_numerator, base_denominator) = Self::rational_parts(base).ok()?;
+        if base_numerator == base_denominator {
+            return Some((Self::from(if negative { -1 } else { 1 }), Self::from(1)));
+        }
+        // a q-th power other than 1 has more than q bits
+        let root = |n: Integer| {
+            if n.is_one() {
+                return Some(n);
+            }
+            if Integer::from(n.bit_length()) <= q {
+                return None;
+            }
+            let (root, rest) = Integer::nth_root(n, u32::try_from(&q).unwrap()).unwrap();
+            if rest.is_zero() {
+                Some(root)
+            } else {
+                None
+            }
+        };
+        let numerator_root: Integer = root(base_numerator)?;
+        let denominator_root: Integer = root(base_denominator)?;
+        let (top, bottom) = if p.sign {
+            (denominator_root, numerator_root)
+        } else {
+            (numerator_root, denominator_root)
+        };
+        let p: Integer = Integer::abs(p);
+        // powers of ten only move the point
+        let strip_tens = |n: Integer| {
+            let mut tidied: Self = Self::from_integer(n);
+            tidied.tidy();
+            (tidied.value, Integer::opposite(tidied.point))
+        };
+        let (top, top_tens) = strip_tens(top);
+        let (bottom, bottom_tens) = strip_tens(bottom);
+        let point: Integer = Integer::mul(Integer::sub(bottom_tens, top_tens), p.clone());
+        if top.is_one() && bottom.is_one() {
+            let mut value: Integer = Integer::one();
+            value.sign = negative;
+            return Some((Self::new(point, value), Self::from(1)));
+        }
+        let top_bits: Integer = Integer::from(top.bit_length());
+        let bottom_bits: Integer = Integer::from(bottom.bit_length());
+        let size: Integer = Integer::mul(
+            p.clone(),
+            Integer::add(top_bits.clone(), bottom_bits.clone()),
+        );
+        if size > Integer::from(EXACT_POWER_BITS) {
+            // a result on a rounding boundary never settles in ziv_round, such a result is a
+            // multiple of the last place kept and takes at most about 11 bits per digit written,
+            // so it is worked out whenever it is within 12 bits per digit of the rounded result
+            let digits: Integer = match context.precision {
+                Precision::SignificantDigits(digits) => Integer::from(digits.get()),
+                Precision::DecimalPlaces(places) => {
+                    // |result| < 2^(p * (top_bits - bottom_bits + 1)) * 10^-point
+                    let log2: Integer = Integer::mul(
+                        p.clone(),
+                        Integer::add(Integer::sub(top_bits, bottom_bits), Integer::one()),
+                    );
+                    let magnitude: Integer = Integer::sub(
+                        Integer::div_floor(
+                            Integer::mul(log2, Integer::from(30103)),
+                            Integer::from(100000),
+                        )
+                        .unwrap(),
+                        Integer::sub(point.clone(), Integer::one()),
+                    );
+                    max(
+                        Integer::add(magnitude, Integer::from(places)),
+                        Integer::zero(),
+                    )
+                }
+            };
+            let lower_size: Integer = Integer::sub(size, Integer::mul(p.clone(), Integer::from(2)));
+            if lower_size > Integer::mul(digits, Integer::from(12)) {
+                return None;
+            }
+        }
+        let mut top: Integer = Integer::pow(top, p.clone()).unwrap();
+        top.sign = negative;
+        Some((
+            Self::new(point, top),
+            Self::from_integer(Integer::pow(bottom, p).unwrap()),
+        ))
+    }
+    pub fn pow(base: Self, exponent: Self, context: &Context) -> Result<Self, ArithmeticError> {
+        // base^exponent correctly rounded under the context, a negative base needs an exponent
+        // whose denominator is odd, then the result is real
+        if exponent.is_zero() {
+            return Ok(Self::round(Self::from(1), context));
+        }
+        if base.is_zero() {
+            if exponent.value.sign {
+                return Err(ArithmeticError::DivisionByZero);
+            }
+            return Ok(Self::round(Self::zero(), context));
+        }
+        let (p, q) = Self::rational_parts(&exponent)?;
+        if base.value.sign && q.is_even() {
+            return Err(ArithmeticError::NegativeRoot);
+        }
+        let negative: bool = base.value.sign && !p.is_even();
+        if let Some((numerator, denominator)) = Self::exact_pow(&base, &exponent, negative, context)
+        {
+            return Self::div_context(numerator, denominator, context);
+        }
+        let magnitude: Self = Self::abs(base);
+        let exponent_size: usize = Self::fixed_from(&Self::abs(exponent.clone()), 0)?.bit_length();
+        Self::ziv_round(context, |bits| {
+            // exp(exponent * ln |base|), the product is kept to bits fraction bits
+            let working: usize = bits + exponent_size + 4;
+            let y: Integer = Self::fixed_from(&exponent, working)?;
+            let ln: Integer = Self::fixed_ln(&magnitude, working);
+            let z: Integer = Integer::shr(Integer::mul(y.clone(), ln.clone()), working);
+            // 1 ulp of y times |ln|, 4 ulps of ln times |y|, 1 from the shift
+            let mut z_error: Integer = Integer::mul(Integer::abs(y), Integer::from(4));
+            z_error.add_assign(&Integer::abs(ln));
+            z_error = Integer::add(Integer::shr(z_error, working), Integer::from(3));
+            let (mantissa, k) = Self::fixed_exp(&z, working);
+            let error: Integer =
+                Integer::add(Integer::mul(z_error, Integer::from(11)), Integer::from(4));
+            Ok(Some(Self::exp_corners(
+                mantissa, error, k, working, negative,
+            )))
+        })
+    }
+}
+
+forward_in_place_operator!(Float, Add, add, AddAssign, add_assign);
+forward_in_place_operator!(Float, Sub, sub, SubAssign, sub_assign);
+forward_binary_operator!(Float, Mul, mul, MulAssign, mul_assign);
+forward_negative_operator!(Float);
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        Float::cmp(self.clone(), other.clone()) == Ordering::Equal
+    }
+}
+impl Eq for Float {}
+impl PartialOrd for Float {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(Ord::cmp(self, other))
+    }
+}
+impl Ord for Float {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Float::cmp(self.clone(), other.clone())
+    }
+}
+impl Hash for Float {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 1.0 and 1.00 must hash alike
+        let mut normalized: Self = self.clone();
+        normalized.tidy();
+        normalized.point.hash(state);
+        normalized.value.hash(state);
+    }
+}
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 0.05, -0.5, 500 and 1.50, the scale is kept
+        let formatted: String = self.format(&FloatFormat::new(Notation::Fixed));
+        match formatted.strip_prefix('-') {
+            Some(magnitude) => f.pad_integral(false, "", magnitude),
+            None => f.pad_integral(true, "", &formatted),
+        }
+    }
+}
+impl fmt::Debug for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+macro_rules! impl_float_exp_format {
+    ($($format_trait:ident, $exponent_char:literal),*) => {
+        $(
+            impl fmt::$format_trait for Float {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    // {:.2e} keeps 3 significant digits, like it does for f64
+                    let mut format: FloatFormat = FloatFormat::new(Notation::Scientific);
+                    if let Some(precision) = f.precision() {
+                        let digits: NonZeroUsize = NonZeroUsize::MIN.saturating_add(precision);
+                        format = format.with_precision(Precision::SignificantDigits(digits));
+                    }
+                    let formatted: String = self.format(&format).replace('e', $exponent_char);
+                    match formatted.strip_prefix('-') {
+                        Some(magnitude) => f.pad_integral(false, "", magnitude),
+                        None => f.pad_integral(true, "", &formatted),
+                    }
+                }
+            }
+        )*
+    };
+}
+impl_float_exp_format!(LowerExp, "e", UpperExp, "E");
+impl FromStr for Float {
+    type Err = ParseNumberError;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Self::parse(string)
+    }
+}
+
+impl From<Integer> for Float {
+    fn from(n: Integer) -> Self {
+        Self::from_integer(n)
+    }
+}
+macro_rules! impl_float_from_primitive {
+    ($($primitive:ty),*) => {
+        $(
+            impl From<$primitive> for Float {
+                fn from(number: $primitive) -> Self {
+                    Self::from_integer(Integer::from(number))
+                }
+            }
+        )*
+    };
+}
+impl_float_from_primitive!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl TryFrom<f64> for Float {
+    type Error = TryFromFloatError;
+    fn try_from(number: f64) -> Result<Self, Self::Error> {
+        Self::from_f64(number)
+    }
+}
+impl TryFrom<f32> for Float {
+    type Error = TryFromFloatError;
+    fn try_from(number: f32) -> Result<Self, Self::Error> {
+        Self::from_f64(number as f64)
+    }
+}
+
+impl Encoding for Float {
+    const KIND: u8 = 1;
+    fn encode_body(self: &Self, out: &mut Vec<u8>) {
+        self.point.encode_body(out);
+        self.value.encode_body(out);
+    }
+    fn decode_body(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let start: usize = reader.position();
+        let point: Integer = Integer::decode_body(reader)?;
+        let value: Integer = Integer::decode_body(reader)?;
+        // zero is always written with point 0
+        if value.is_zero() && !point.is_zero() {
+            return Err(DecodeError::new(DecodeErrorKind::NonCanonical, start));
+        }
+        Ok(Self::new(point, value))
+    }
+    fn to_json_value(self: &Self) -> Json {
+        // "1.50" while the point is near the digits, "5e3" or "5e-30" otherwise,
+        // both parse back to the same value and point
+        let digits: String = Integer::abs(self.value.clone()).to_string();
+        let sign: &str = if self.value.sign { "-" } else { "" };
+        let text: String = match usize::try_from(&self.point) {
+            Ok(0) => format!("{}{}", sign, digits),
+            Ok(point) if point <= digits.len() + DECIMAL_CHUNK_WIDTH => {
+                let padded: String = format!("{:0>width$}", digits, width = point + 1);
+                let (integral, fractional) = padded.split_at(padded.len() - point);
+                format!("{}{}.{}", sign, integral, fractional)
+            }
+            _ => format!(
+                "{}{}e{}",
+                sign,
+                digits,
+                Integer::opposite(self.point.clone())
+            ),
+        };
+        Json::string(text)
+    }
+    fn from_json_value(json: &Json) -> Result<Self, DecodeError> {
+        // any point an arithmetic result can reach, not just the ones parse accepts
+        Self::parse_within(json.as_str()?, None).map_err(|error| {
+            DecodeError::new(
+                DecodeErrorKind::InvalidValue,
+                json.position + 1 + error.position(),
+            )
+        })
+    }
+}
+
+/* Symbol */
+
+// #[derive(Clone)]
+// pub struct Symbol {
+//     redefined: bool,
+//     symbol: String,
+// }
+
+// impl Symbol {
+//     pub fn new(redefined: bool, symbol: String) -> Self {
+//         Self {
+//             redefined: redefined,
+//             symbol: symbol,
+//         }
+//     }
+//     fn get_value(self: Self) -> Integer {}
+// }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashSet};
+
+    fn random_digits(seed: &mut u64, len: usize) -> Vec<Digit> {
+        // xorshift64, the same digits on every run
+        (0..len)
+            .map(|_| {
+                *seed ^= *seed << 13;
+                *seed ^= *seed >> 7;
+                *seed ^= *seed << 17;
+                (*seed >> 16) as Digit
+            })
+            .collect()
+    }
+    fn random_integer(seed: &mut u64, len: usize) -> Integer {
+        let mut digits: Vec<Digit> = random_digits(seed, len);
+        // keep the length, the top digit is never zero
+        digits[len - 1] |= 1;
+        Integer::from_digits(false, digits)
+    }
+    fn schoolbook_mul(a: &Integer, b: &Integer) -> Integer {
+        let mut digits: Vec<Digit> = Vec::new();
+        Integer::digits_mul_basecase(&a.digits, &b.digits, &mut digits);
+        let mut product: Integer = Integer::from_digits(false, digits);
+        product.sign = a.sign != b.sign && !product.is_digits_zero();
+        product
+    }
+
+    /* Limbs and decimal conversion */
+
+    fn naive_decimal(n: &Integer) -> String {
+        // one short division by 10^9 per chunk, never takes the divide-and-conquer path
+        let mut chunks: Vec<Digit> = Vec::new();
+        let mut rest: Integer = Integer::abs(n.clone());
+        while !rest.is_digits_zero() {
+            let (quotient, chunk) = Integer::digits_short_div(rest, DECIMAL_CHUNK_RADIX);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+        let mut decimal: String = match chunks.pop() {
+            Some(top) => top.to_string(),
+            None => String::from("0"),
+        };
+        for chunk in chunks.iter().rev() {
+            decimal.push_str(&format!("{:09}", chunk));
+        }
+        if n.sign {
+            decimal.insert(0, '-');
+        }
+        decimal
+    }
+    fn naive_from_decimal(decimal: &str) -> Integer {
+        let mut result: Integer = Integer::zero();
+        for byte in decimal.bytes() {
+            result.digits_mul_add_small(DECIMAL_RADIX as Digit, (byte - b'0') as Digit);
+        }
+        result.tidy();
+        result
+    }
+
+    #[test]
+    fn decimal_round_trip_across_conversion_threshold() {
+        let mut seed: u64 = 0x6a09_e667_f3bc_c908;
+        for len in [
+            1,
+            RADIX_CONVERSION_THRESHOLD - 1,
+            RADIX_CONVERSION_THRESHOLD,
+            RADIX_CONVERSION_THRESHOLD + 1,
+            RADIX_CONVERSION_THRESHOLD * 2 + 3,
+            RADIX_CONVERSION_THRESHOLD * 9,
+        ] {
+            let mut n: Integer = random_integer(&mut seed, len);
+            n.sign = len % 2 == 0;
+            let decimal: String = n.to_string();
+            assert_eq!(decimal, naive_decimal(&n), "{} digits", len);
+            assert_eq!(integer(&decimal), n, "{} digits", len);
+        }
+    }
+    #[test]
+    fn decimal_parse_across_conversion_threshold() {
+        // the chunk threshold is counted in groups of DECIMAL_CHUNK_WIDTH decimal digits
+        let mut seed: u64 = 0xbb67_ae85_84ca_a73b;
+        let chunk_threshold: usize = RADIX_CONVERSION_THRESHOLD * DECIMAL_CHUNK_WIDTH;
+        for len in [
+            chunk_threshold - 1,
+            chunk_threshold,
+            chunk_threshold + 1,
+            chunk_threshold * 2 + DECIMAL_CHUNK_WIDTH + 4,
+            chunk_threshold * 7,
+        ] {
+            let mut decimal: String = random_digits(&mut seed, len)
+                .iter()
+                .map(|&digit| char::from(b'0' + (digit % 10) as u8))
+                .collect();
+            decimal.replace_range(0..1, "7");
+            let n: Integer = integer(&decimal);
+            assert_eq!(n, naive_from_decimal(&decimal), "{} decimal digits", len);
+            assert_eq!(n.to_string(), decimal, "{} decimal digits", len);
+        }
+    }
+    #[test]
+    fn decimal_round_trip_of_powers_of_ten() {
+        // every chunk below the top one is zero
+        for exp in [0, 8, 9, 10, 287, 288, 289, 1000] {
+            let decimal: String = format!("1{}", "0".repeat(exp));
+            let n: Integer = Integer::pow10(exp);
+            assert_eq!(n.to_string(), decimal, "10^{}", exp);
+            assert_eq!(integer(&decimal), n, "10^{}", exp);
+            let below: Integer = Integer::sub(n, Integer::one());
+            assert_eq!(below.to_string(), naive_decimal(&below), "10^{} - 1", exp);
+        }
+    }
+    #[test]
+    fn karatsuba_mul_matches_schoolbook() {
+        let mut seed: u64 = 0x3c6e_f372_fe94_f82b;
+        for &(a_len, b_len) in [
+            (1, 1),
+            (KARATSUBA_THRESHOLD - 1, KARATSUBA_THRESHOLD - 1),
+            (KARATSUBA_THRESHOLD, KARATSUBA_THRESHOLD),
+            (KARATSUBA_THRESHOLD + 1, KARATSUBA_THRESHOLD),
+            (KARATSUBA_THRESHOLD * 2 + 1, KARATSUBA_THRESHOLD + 3),
+            (KARATSUBA_THRESHOLD * 4 - 1, KARATSUBA_THRESHOLD * 4 - 1),
+            (KARATSUBA_THRESHOLD, 3),
+        ]
+        .iter()
+        {
+            let mut a: Integer = random_integer(&mut seed, a_len);
+            let b: Integer = random_integer(&mut seed, b_len);
+            a.sign = b_len % 2 == 1;
+            let expected: Integer = schoolbook_mul(&a, &b);
+            assert_eq!(
+                Integer::mul(a.clone(), b.clone()),
+                expected,
+                "{} x {}",
+                a_len,
+                b_len
+            );
+            assert_eq!(Integer::mul(b, a), expected, "{} x {}", b_len, a_len);
+        }
+    }
+    #[test]
+    fn carry_and_borrow_run_across_every_digit() {
+        for k in [1, 2, 3, KARATSUBA_THRESHOLD, RADIX_CONVERSION_THRESHOLD * 3] {
+            let all_ones: Integer = Integer::from_digits(false, vec![Digit::MAX; k]);
+            let power: Integer = Integer::shl(Integer::one(), k * DIGIT_BITS as usize);
+            // (2^(32k) - 1) + 1 = 2^(32k)
+            let sum: Integer = Integer::add(all_ones.clone(), Integer::one());
+            assert_eq!(sum, power, "k = {}", k);
+            assert_eq!(sum.digits.len(), k + 1, "k = {}", k);
+            // 2^(32k) - 1 borrows through every digit
+            let difference: Integer = Integer::sub(power.clone(), Integer::one());
+            assert_eq!(difference, all_ones, "k = {}", k);
+            assert_eq!(difference.digits.len(), k, "k = {}", k);
+            // -1 - (2^(32k) - 1) = -2^(32k)
+            let negative: Integer = Integer::sub(integer("-1"), all_ones.clone());
+            assert_eq!(negative, Integer::opposite(power.clone()), "k = {}", k);
+            assert_eq!(naive_decimal(&power), power.to_string(), "k = {}", k);
+        }
+    }
+
+    /* Signed division */
+
+    fn integer(string: &str) -> Integer {
+        string.parse::<Integer>().unwrap()
+    }
+    fn check_division(
+        divide: fn(Integer, Integer) -> Result<(Integer, Integer), ArithmeticError>,
+        cases: &[(&str, &str, &str, &str)],
+    ) {
+        // (a, b, quotient, remainder), and a = quotient * b + remainder every time
+        for &(a, b, quotient, remainder) in cases {
+            let (q, r) = divide(integer(a), integer(b)).unwrap();
+            assert_eq!(
+                (q.to_string(), r.to_string()),
+                (quotient.to_string(), remainder.to_string()),
+                "{} / {}",
+                a,
+                b
+            );
+        }
+    }
+    #[test]
+    fn truncated_division_rounds_toward_zero() {
+        // the remainder takes the sign of a
+        check_division(
+            Integer::div_rem,
+            &[
+                ("7", "2", "3", "1"),
+                ("-7", "2", "-3", "-1"),
+                ("7", "-2", "-3", "1"),
+                ("-7", "-2", "3", "-1"),
+                ("-6", "3", "-2", "0"),
+                ("-1", "5", "0", "-1"),
+                (
+                    "-100000000000000000000000000001",
+                    "10000000000000",
+                    "-10000000000000000",
+                    "-1",
+                ),
+            ],
+        );
+    }
+    #[test]
+    fn floored_division_rounds_toward_minus_infinity() {
+        // the remainder takes the sign of b
+        check_division(
+            Integer::div_mod_floor,
+            &[
+                ("7", "2", "3", "1"),
+                ("-7", "2", "-4", "1"),
+                ("7", "-2", "-4", "-1"),
+                ("-7", "-2", "3", "-1"),
+                ("-6", "3", "-2", "0"),
+                ("-1", "5", "-1", "4"),
+                (
+                    "-100000000000000000000000000001",
+                    "10000000000000",
+                    "-10000000000000001",
+                    "9999999999999",
+                ),
+            ],
+        );
+    }
+    #[test]
+    fn euclidean_division_keeps_the_remainder_non_negative() {
+        check_division(
+            Integer::div_rem_euclid,
+            &[
+                ("7", "2", "3", "1"),
+                ("-7", "2", "-4", "1"),
+                ("7", "-2", "-3", "1"),
+                ("-7", "-2", "4", "1"),
+                ("-6", "-3", "2", "0"),
+                ("-1", "-5", "1", "4"),
+                (
+                    "-100000000000000000000000000001",
+                    "-10000000000000",
+                    "10000000000000001",
+                    "9999999999999",
+                ),
+            ],
+        );
+    }
+    #[test]
+    fn division_by_zero_and_exact_division() {
+        for divide in [
+            Integer::div_rem,
+            Integer::div_mod_floor,
+            Integer::div_rem_euclid,
+        ] {
+            assert!(matches!(
+                divide(integer("7"), integer("0")),
+                Err(ArithmeticError::DivisionByZero)
+            ));
+            assert!(matches!(
+                divide(integer("0"), integer("-0")),
+                Err(ArithmeticError::DivisionByZero)
+            ));
+        }
+        assert!(matches!(
+            Integer::div_exact(integer("5"), integer("0")),
+            Err(ArithmeticError::DivisionByZero)
+        ));
+        assert_eq!(
+            Integer::div_exact(integer("-123456789012345678901234"), integer("2"))
+                .unwrap()
+                .to_string(),
+            "-61728394506172839450617"
+        );
+        assert_eq!(
+            Integer::div_exact(integer("-12"), integer("-4"))
+                .unwrap()
+                .to_string(),
+            "3"
+        );
+        assert_eq!(
+            Integer::div_exact(integer("0"), integer("-4"))
+                .unwrap()
+                .to_string(),
+            "0"
+        );
+    }
+
+    /* Exponentiation */
+
+    #[test]
+    fn pow_by_squaring() {
+        assert_eq!(
+            Integer::pow(integer("0"), integer("0"))
+                .unwrap()
+                .to_string(),
+            "1"
+        );
+        assert_eq!(
+            Integer::pow(integer("-3"), integer("5"))
+                .unwrap()
+                .to_string(),
+            "-243"
+        );
+        assert_eq!(
+            Integer::pow(integer("-3"), integer("4"))
+                .unwrap()
+                .to_string(),
+            "81"
+        );
+        assert_eq!(
+            Integer::pow(integer("2"), integer("100"))
+                .unwrap()
+                .to_string(),
+            "1267650600228229401496703205376"
+        );
+        assert!(matches!(
+            Integer::pow(integer("2"), integer("-1")),
+            Err(ArithmeticError::NegativeExponent)
+        ));
+    }
+    #[test]
+    fn pow_mod_with_negative_operands() {
+        // the result is always in [0, |modulus|)
+        let pow_mod = |base: &str, exp: &str, modulus: &str| {
+            Integer::pow_mod(integer(base), integer(exp), integer(modulus)).map(|n| n.to_string())
+        };
+        assert_eq!(pow_mod("4", "13", "497"), Ok("445".to_string()));
+        assert_eq!(pow_mod("-4", "13", "497"), Ok("52".to_string()));
+        assert_eq!(pow_mod("4", "13", "-497"), Ok("445".to_string()));
+        assert_eq!(pow_mod("5", "0", "1"), Ok("0".to_string()));
+        // a negative exponent goes through the inverse, 3^-1 = 4 (mod 11)
+        assert_eq!(pow_mod("3", "-1", "11"), Ok("4".to_string()));
+        assert_eq!(pow_mod("3", "-2", "11"), Ok("5".to_string()));
+        assert_eq!(pow_mod("-3", "-3", "-11"), Ok("2".to_string()));
+        assert_eq!(pow_mod("6", "-1", "9"), Err(ArithmeticError::NotInvertible));
+        assert_eq!(pow_mod("3", "5", "0"), Err(ArithmeticError::DivisionByZero));
+    }
+
+    /* Greatest common divisor */
+
+    #[test]
+    fn gcd_and_lcm_are_non_negative() {
+        let gcd = |a: &str, b: &str| Integer::gcd(integer(a), integer(b)).to_string();
+        let lcm = |a: &str, b: &str| Integer::lcm(integer(a), integer(b)).to_string();
+        assert_eq!(gcd("-12", "18"), "6");
+        assert_eq!(gcd("12", "-18"), "6");
+        assert_eq!(gcd("0", "-7"), "7");
+        assert_eq!(gcd("0", "0"), "0");
+        assert_eq!(lcm("-4", "6"), "12");
+        assert_eq!(lcm("0", "-6"), "0");
+        // 2^64 + 1 = 274177 * 67280421310721
+        assert_eq!(
+            gcd("18446744073709551617", "67280421310721000"),
+            "67280421310721"
+        );
+    }
+    #[test]
+    fn extended_gcd_with_negative_operands() {
+        // a * x + b * y = g for every sign combination
+        for (a, b) in [
+            ("240", "46"),
+            ("-240", "46"),
+            ("240", "-46"),
+            ("-240", "-46"),
+            ("0", "-5"),
+            ("-5", "0"),
+        ] {
+            let (g, x, y) = Integer::extended_gcd(integer(a), integer(b));
+            let g: String = g.to_string();
+            let sum: Integer =
+                Integer::add(Integer::mul(integer(a), x), Integer::mul(integer(b), y));
+            assert_eq!(sum.to_string(), g, "{} {}", a, b);
+            assert_eq!(Integer::gcd(integer(a), integer(b)).to_string(), g);
+        }
+    }
+    #[test]
+    fn extended_gcd_of_large_operands() {
+        // many digits take the lehmer path, consecutive fibonacci numbers need the most steps
+        let mut seed: u64 = 0x510e_527f_ade6_82d1;
+        let common: Integer = random_integer(&mut seed, 7);
+        let mut pairs: Vec<(Integer, Integer)> = vec![
+            (Integer::fibonacci(6000), Integer::fibonacci(5999)),
+            (
+                Integer::fibonacci(3001),
+                Integer::opposite(Integer::fibonacci(3000)),
+            ),
+        ];
+        for (a_len, b_len) in [(3, 3), (40, 40), (120, 90), (90, 120), (200, 5)] {
+            let a: Integer = random_integer(&mut seed, a_len);
+            let b: Integer = random_integer(&mut seed, b_len);
+            pairs.push((a.clone(), b.clone()));
+            pairs.push((
+                Integer::mul(a, common.clone()),
+                Integer::mul(b, common.clone()),
+            ));
+        }
+        for (a, b) in pairs {
+            let (g, x, y) = Integer::extended_gcd(a.clone(), b.clone());
+            assert_eq!(g, Integer::gcd(a.clone(), b.clone()));
+            assert_eq!(
+                Integer::add(
+                    Integer::mul(a.clone(), x.clone()),
+                    Integer::mul(b.clone(), y)
+                ),
+                g
+            );
+            // the cofactor stays below |b| / g, so it never grows with the step count
+            let bound: Integer = Integer::div(Integer::abs(b.clone()), g.clone()).unwrap();
+            assert!(Integer::cmp_abs(&x, &bound) != Ordering::Greater);
+        }
+        let modulus: Integer = Integer::fibonacci(4000);
+        let a: Integer = Integer::fibonacci(3999);
+        let inverse: Integer = Integer::mod_inverse(a.clone(), modulus.clone()).unwrap();
+        assert!(Integer::mod_floor(Integer::mul(a, inverse), modulus)
+            .unwrap()
+            .is_one());
+    }
+    #[test]
+    fn mod_inverse_with_negative_moduli() {
+        // the inverse lies in [0, |modulus|) whatever the signs
+        let inverse = |a: &str, modulus: &str| {
+            Integer::mod_inverse(integer(a), integer(modulus)).map(|n| n.to_string())
+        };
+        assert_eq!(inverse("3", "11"), Ok("4".to_string()));
+        assert_eq!(inverse("3", "-11"), Ok("4".to_string()));
+        assert_eq!(inverse("-3", "-11"), Ok("7".to_string()));
+        assert_eq!(inverse("-3", "11"), Ok("7".to_string()));
+        assert_eq!(inverse("5", "1"), Ok("0".to_string()));
+        assert_eq!(inverse("6", "-9"), Err(ArithmeticError::NotInvertible));
+        assert_eq!(inverse("3", "0"), Err(ArithmeticError::DivisionByZero));
+    }
+
+    /* Roots */
+
+    #[test]
+    fn nth_root_of_negative_values() {
+        // an odd root keeps the sign and rounds toward zero, n = root^k + remainder
+        let root = |n: &str, k: u32| {
+            Integer::nth_root(integer(n), k)
+                .map(|(root, rest)| (root.to_string(), rest.to_string()))
+        };
+        assert_eq!(root("-27", 3), Ok(("-3".to_string(), "0".to_string())));
+        assert_eq!(root("-28", 3), Ok(("-3".to_string(), "-1".to_string())));
+        assert_eq!(root("-26", 3), Ok(("-2".to_string(), "-18".to_string())));
+        assert_eq!(root("-1", 5), Ok(("-1".to_string(), "0".to_string())));
+        assert_eq!(root("-7", 1), Ok(("-7".to_string(), "0".to_string())));
+        assert_eq!(root("-4", 2), Err(ArithmeticError::NegativeRoot));
+        assert_eq!(root("8", 0), Err(ArithmeticError::DivisionByZero));
+        // from k = bit_length(n) on the root is 1, whatever the size of k
+        assert_eq!(root("100", 7), Ok(("1".to_string(), "99".to_string())));
+        assert_eq!(root("100", 6), Ok(("2".to_string(), "36".to_string())));
+        assert_eq!(
+            root("100", 50_000_000),
+            Ok(("1".to_string(), "99".to_string()))
+        );
+        assert_eq!(
+            root("-100", u32::MAX),
+            Ok(("-1".to_string(), "-99".to_string()))
+        );
+        assert_eq!(root("0", u32::MAX), Ok(("0".to_string(), "0".to_string())));
+        assert_eq!(root("0", 2), Ok(("0".to_string(), "0".to_string())));
+        assert_eq!(
+            root("1", u32::MAX - 1),
+            Ok(("1".to_string(), "0".to_string()))
+        );
+        // floor(sqrt(10^40 - 1)) = 10^20 - 1
+        assert_eq!(
+            root("9999999999999999999999999999999999999999", 2),
+            Ok((
+                "99999999999999999999".to_string(),
+                "199999999999999999998".to_string()
+            ))
+        );
+        assert!(Integer::is_perfect_square(integer(
+            "152415787532388367504942236884722755800955129"
+        )));
+        assert!(!Integer::is_perfect_square(integer("-4")));
+        assert!(!Integer::is_perfect_square(integer(
+            "152415787532388367504942236884722755800955130"
+        )));
+    }
+    #[test]
+    fn perfect_power_of_negative_values() {
+        // a negative n can only be an odd power
+        let power =
+            |n: &str| Integer::perfect_power(integer(n)).map(|(base, exp)| (base.to_string(), exp));
+        assert_eq!(power("64"), Some(("2".to_string(), 6)));
+        assert_eq!(power("-64"), Some(("-4".to_string(), 3)));
+        assert_eq!(power("-32"), Some(("-2".to_string(), 5)));
+        assert_eq!(power("-16"), None);
+        assert_eq!(power("-1"), None);
+        assert_eq!(power("0"), None);
+        assert_eq!(power("-2187"), Some(("-3".to_string(), 7)));
+        assert_eq!(power("3486784401"), Some(("3".to_string(), 20)));
+        assert_eq!(power("3486784402"), None);
+    }
+
+    /* Operators and comparison traits */
+
+    fn float(string: &str) -> Float {
+        string.parse::<Float>().unwrap()
+    }
+    #[test]
+    fn operators_on_values_and_references() {
+        let a: Integer = integer("123456789012345678901234567890");
+        let b: Integer = integer("-987654321");
+        assert_eq!(&a + &b, integer("123456789012345678900246913569"));
+        assert_eq!(a.clone() - &b, integer("123456789012345678902222222211"));
+        assert_eq!(
+            &a * b.clone(),
+            integer("-121932631124828532112482853211126352690")
+        );
+        assert_eq!(-&b, integer("987654321"));
+        let mut c: Integer = a.clone();
+        c += &b;
+        c -= b.clone();
+        c *= integer("-1");
+        assert_eq!(c, -a);
+        let x: Float = float("1.25");
+        let y: Float = float("-0.5");
+        assert_eq!(&x + &y, float("0.75"));
+        assert_eq!(x.clone() - y.clone(), float("1.75"));
+        assert_eq!(&x * &y, float("-0.625"));
+        assert_eq!(-y, float("0.5"));
+    }
+    #[test]
+    fn equality_ordering_and_hash_follow_the_value() {
+        // 1.0 == 1.00 and both land on the same map entry
+        assert_eq!(float("1.0"), float("1.00"));
+        assert_eq!(float("-0.0"), float("0"));
+        assert!(float("-2.5") < float("-2.49"));
+        assert!(float("0.001") > float("-1000"));
+        assert!(integer("-10000000000000000000000") < integer("-9999999999999999999999"));
+        let mut set: HashSet<Float> = HashSet::new();
+        set.insert(float("1.0"));
+        assert!(set.contains(&float("1.000")));
+        let mut map: BTreeMap<Integer, &str> = BTreeMap::new();
+        map.insert(integer("3"), "three");
+        map.insert(integer("-20"), "minus twenty");
+        map.insert(integer("100000000000000000000"), "big");
+        assert_eq!(
+            map.values().copied().collect::<Vec<&str>>(),
+            ["minus twenty", "three", "big"]
+        );
+    }
+    #[test]
+    fn display_from_str_and_from_primitives() {
+        assert_eq!(
+            Integer::from(i128::MIN).to_string(),
+            "-170141183460469231731687303715884105728"
+        );
+        assert_eq!(Integer::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(Integer::from(-7i8), integer("-7"));
+        assert_eq!(format!("{:>6}", integer("-42")), "   -42");
+        assert_eq!(format!("{:+}", integer("42")), "+42");
+        assert_eq!(float("-0.050").to_string(), "-0.050");
+        assert_eq!(float("500").to_string(), "500");
+        assert_eq!(format!("{:08}", float("-1.5")), "-00001.5");
+        assert_eq!(format!("{:?}", float("2.50")), "2.50");
+    }
+
+    /* Parsing */
+
+    fn parse_error<T: FromStr<Err = ParseNumberError>>(
+        string: &str,
+    ) -> (ParseNumberErrorKind, usize) {
+        match string.parse::<T>() {
+            Ok(_) => panic!("{:?} should not parse", string),
+            Err(error) => (error.kind(), error.position()),
+        }
+    }
+    #[test]
+    fn integer_syntax() {
+        assert_eq!(integer("+1_000_000"), Integer::from(1_000_000));
+        assert_eq!(integer("-0xFf"), Integer::from(-255));
+        assert_eq!(integer("0o17"), Integer::from(15));
+        assert_eq!(integer("-0B1010_1010"), Integer::from(-170));
+        assert_eq!(integer("-0"), Integer::zero());
+        use ParseNumberErrorKind::*;
+        assert_eq!(parse_error::<Integer>(""), (Empty, 0));
+        assert_eq!(parse_error::<Integer>("-"), (Empty, 1));
+        assert_eq!(parse_error::<Integer>("+0x"), (Empty, 3));
+        assert_eq!(parse_error::<Integer>("12a4"), (InvalidDigit, 2));
+        assert_eq!(parse_error::<Integer>("0b102"), (InvalidDigit, 4));
+        assert_eq!(parse_error::<Integer>("_1"), (InvalidDigit, 0));
+        assert_eq!(parse_error::<Integer>("1__2"), (InvalidDigit, 1));
+        assert_eq!(parse_error::<Integer>("0x_f"), (InvalidDigit, 2));
+        assert_eq!(parse_error::<Integer>("12_"), (InvalidDigit, 2));
+        assert_eq!(parse_error::<Integer>("--1"), (InvalidDigit, 1));
+        assert_eq!(parse_error::<Integer>("1.5"), (InvalidDigit, 1));
+    }
+    #[test]
+    fn float_syntax() {
+        assert_eq!(float("6.02e23"), float("602000000000000000000000"));
+        assert_eq!(float("+1_000.000_1"), float("1000.0001"));
+        assert_eq!(float("-.5E-2"), float("-0.005"));
+        assert_eq!(float("1."), float("1"));
+        assert_eq!(float("-0x1f"), float("-31"));
+        assert_eq!(float("1e999999999").to_string(), "1e999999999");
+        use ParseNumberErrorKind::*;
+        assert_eq!(parse_error::<Float>(""), (Empty, 0));
+        assert_eq!(parse_error::<Float>("-."), (Empty, 2));
+        assert_eq!(parse_error::<Float>("1e"), (Empty, 2));
+        assert_eq!(parse_error::<Float>("1e+"), (Empty, 3));
+        assert_eq!(parse_error::<Float>("1.2.3"), (InvalidDigit, 3));
+        assert_eq!(parse_error::<Float>("1e5x"), (InvalidDigit, 3));
+        assert_eq!(parse_error::<Float>("1e_5"), (InvalidDigit, 2));
+        assert_eq!(parse_error::<Float>("0x1.8"), (InvalidDigit, 3));
+        // an out of range point is reported at the digits that set it
+        assert_eq!(parse_error::<Float>("1e1000000000"), (OutOfRange, 2));
+        assert_eq!(parse_error::<Float>("1e-1000000000"), (OutOfRange, 3));
+        assert_eq!(parse_error::<Float>("-25E+1000000000"), (OutOfRange, 5));
+        assert_eq!(parse_error::<Float>("0.5e-999999999"), (OutOfRange, 5));
+    }
+
+    /* Radix conversion */
+
+    #[test]
+    fn string_radix_round_trips() {
+        let values: Vec<Integer> = vec![
+            Integer::zero(),
+            integer("-1"),
+            integer("35"),
+            integer("4294967296"),
+            integer("-340282366920938463463374607431768211455"),
+            Integer::pow(integer("-7"), integer("301")).unwrap(),
+        ];
+        for radix in 2..=36 {
+            for n in values.iter() {
+                let string: String = n.to_string_radix(radix);
+                assert_eq!(
+                    &Integer::from_str_radix(&string, radix).unwrap(),
+                    n,
+                    "radix {}",
+                    radix
+                );
+                assert_eq!(
+                    Integer::from_str_radix(&string.to_uppercase(), radix).unwrap(),
+                    *n
+                );
+            }
+        }
+        assert_eq!(integer("255").to_string_radix(16), "ff");
+        assert_eq!(integer("-35").to_string_radix(36), "-z");
+        assert_eq!(integer("-5").to_string_radix(2), "-101");
+        assert_eq!(Integer::from_str_radix("z", 35).unwrap_err().position(), 0);
+        assert_eq!(Integer::from_str_radix("1_0", 2), Ok(integer("2")));
+    }
+    #[test]
+    fn radix_digit_round_trips() {
+        // any radix from 2 up, the digits come lowest first
+        let n: Integer = Integer::pow(integer("-3"), integer("1001")).unwrap();
+        for radix in [
+            "2",
+            "10",
+            "36",
+            "4294967296",
+            "18446744073709551617",
+            "1000000000000000000000000000000",
+        ] {
+            let (sign, digits) = Integer::to_radix_digits(n.clone(), integer(radix));
+            assert!(sign);
+            assert!(digits
+                .iter()
+                .all(|digit| *digit >= Integer::zero() && *digit < integer(radix)));
+            assert_ne!(digits.last(), Some(&Integer::zero()));
+            assert_eq!(
+                Integer::from_radix_digits(sign, digits, integer(radix)),
+                n,
+                "radix {}",
+                radix
+            );
+        }
+        let (sign, digits) = Integer::to_radix_digits(integer("1234"), integer("100"));
+        assert!(!sign);
+        assert_eq!(digits, [integer("34"), integer("12")]);
+        assert_eq!(
+            Integer::to_radix_digits(Integer::zero(), integer("7")),
+            (false, vec![Integer::zero()])
+        );
+    }
+
+    /* Bit operations */
+
+    #[test]
+    fn bit_operations_match_i64() {
+        // two's complement on both sides, the values stay inside i64
+        let values: [i64; 12] = [
+            0,
+            1,
+            -1,
+            2,
+            -2,
+            255,
+            -256,
+            4294967295,
+            -4294967296,
+            0x1234_5678_9abc_def0,
+            i64::MAX,
+            i64::MIN,
+        ];
+        for &a in values.iter() {
+            let x: Integer = Integer::from(a);
+            assert_eq!(Integer::not(x.clone()), Integer::from(!a), "!{}", a);
+            for shift in [0usize, 1, 5, 31, 32, 33, 63] {
+                assert_eq!(
+                    Integer::shr(x.clone(), shift),
+                    Integer::from(a >> shift),
+                    "{} >> {}",
+                    a,
+                    shift
+                );
+                assert_eq!(
+                    Integer::shl(x.clone(), shift),
+                    Integer::from((a as i128) << shift),
+                    "{} << {}",
+                    a,
+                    shift
+                );
+            }
+            for index in [0usize, 1, 31, 32, 62, 63, 64, 100] {
+                assert_eq!(
+                    x.test_bit(index),
+                    (a >> index.min(63)) & 1 == 1,
+                    "bit {} of {}",
+                    index,
+                    a
+                );
+            }
+            for &b in values.iter() {
+                let y: Integer = Integer::from(b);
+                assert_eq!(
+                    Integer::and(x.clone(), y.clone()),
+                    Integer::from(a & b),
+                    "{} & {}",
+                    a,
+                    b
+                );
+                assert_eq!(
+                    Integer::or(x.clone(), y.clone()),
+                    Integer::from(a | b),
+                    "{} | {}",
+                    a,
+                    b
+                );
+                assert_eq!(
+                    Integer::xor(x.clone(), y.clone()),
+                    Integer::from(a ^ b),
+                    "{} ^ {}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+    #[test]
+    fn bit_counts_and_set_bit() {
+        assert_eq!(integer("0").bit_length(), 0);
+        assert_eq!(integer("-4294967296").bit_length(), 33);
+        assert_eq!(integer("-7").count_ones(), 3);
+        assert_eq!(integer("-96").trailing_zeros(), Some(5));
+        assert_eq!(integer("0").trailing_zeros(), None);
+        let mut n: Integer = integer("-1");
+        n.set_bit(40, false);
+        assert_eq!(n, Integer::from(!(1i64 << 40)));
+        n.set_bit(40, true);
+        assert_eq!(n, integer("-1"));
+        let mut n: Integer = Integer::zero();
+        n.set_bit(70, true);
+        n.set_bit(0, true);
+        assert_eq!(n, integer("1180591620717411303425"));
+        n.set_bit(70, false);
+        assert_eq!(n, integer("1"));
+    }
+
+    /* Primitive conversions */
+
+    #[test]
+    fn try_from_at_the_bounds_of_every_primitive() {
+        assert_eq!(i8::try_from(integer("127")), Ok(127));
+        assert_eq!(i8::try_from(integer("128")), Err(TryFromIntegerError));
+        assert_eq!(i8::try_from(integer("-128")), Ok(-128));
+        assert_eq!(i8::try_from(integer("-129")), Err(TryFromIntegerError));
+        assert_eq!(u8::try_from(integer("255")), Ok(255));
+        assert_eq!(u8::try_from(integer("256")), Err(TryFromIntegerError));
+        assert_eq!(u8::try_from(integer("-1")), Err(TryFromIntegerError));
+        assert_eq!(u32::try_from(integer("4294967295")), Ok(u32::MAX));
+        assert_eq!(
+            u32::try_from(integer("4294967296")),
+            Err(TryFromIntegerError)
+        );
+        assert_eq!(
+            i64::try_from(&integer("-9223372036854775808")),
+            Ok(i64::MIN)
+        );
+        assert_eq!(
+            i64::try_from(&integer("9223372036854775808")),
+            Err(TryFromIntegerError)
+        );
+        assert_eq!(
+            u64::try_from(integer("18446744073709551616")),
+            Err(TryFromIntegerError)
+        );
+        assert_eq!(i128::try_from(Integer::from(i128::MIN)), Ok(i128::MIN));
+        assert_eq!(
+            i128::try_from(Integer::add(Integer::from(i128::MAX), Integer::one())),
+            Err(TryFromIntegerError)
+        );
+        assert_eq!(u128::try_from(Integer::from(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(u128::try_from(Integer::from(-1)), Err(TryFromIntegerError));
+        assert_eq!(usize::try_from(integer("0")), Ok(0));
+        assert_eq!(isize::try_from(integer("-1")), Ok(-1));
+        // every primitive comes back unchanged
+        for n in [0i64, 1, -1, 10, -10, 100, i64::MIN, i64::MAX] {
+            assert_eq!(Integer::from(n).to_isize(), n as isize);
+            assert_eq!(i64::try_from(Integer::from_isize(n as isize)), Ok(n));
+        }
+    }
+    #[test]
+    fn integer_to_f64_rounds_half_to_even() {
+        // 2^53 + 1 and 2^53 + 3 are ties between neighbouring doubles
+        assert_eq!(integer("9007199254740993").to_f64(), 9007199254740992.0);
+        assert_eq!(integer("9007199254740995").to_f64(), 9007199254740996.0);
+        assert_eq!(integer("-9007199254740993").to_f64(), -9007199254740992.0);
+        assert_eq!(
+            integer("9007199254740993000000000000000000001").to_f64(),
+            9.007199254740993e36
+        );
+        assert_eq!(integer("0").to_f64(), 0.0);
+        // f64::MAX plus half an ulp is a tie that rounds to infinity, one less stays finite
+        let half_ulp: Integer = Integer::shl(Integer::one(), 970);
+        let max: Integer = Integer::shl(Integer::from((1u64 << 53) - 1), 971);
+        assert_eq!(
+            Integer::add(max.clone(), half_ulp.clone()).to_f64(),
+            f64::INFINITY
+        );
+        assert_eq!(
+            Integer::sub(Integer::add(max.clone(), half_ulp), Integer::one()).to_f64(),
+            f64::MAX
+        );
+        assert_eq!(Integer::opposite(max).to_f64(), f64::MIN);
+        assert_eq!(Integer::shl(Integer::one(), 1024).to_f64(), f64::INFINITY);
+    }
+
+    /* Multiplication */
+
+    const MUL_LENGTHS: [(usize, usize); 12] = [
+        (TOOM_3_THRESHOLD - 1, TOOM_3_THRESHOLD - 1),
+        (TOOM_3_THRESHOLD, TOOM_3_THRESHOLD),
+        (TOOM_3_THRESHOLD + 1, TOOM_3_THRESHOLD),
+        (TOOM_3_THRESHOLD, TOOM_3_THRESHOLD * 2),
+        (TOOM_3_THRESHOLD, TOOM_3_THRESHOLD * 2 + 1),
+        (TOOM_3_THRESHOLD * 3 + 2, TOOM_3_THRESHOLD * 2 - 7),
+        (NTT_THRESHOLD - 1, NTT_THRESHOLD - 1),
+        (NTT_THRESHOLD, NTT_THRESHOLD),
+        (NTT_THRESHOLD, NTT_THRESHOLD + 1),
+        (NTT_THRESHOLD + 5, NTT_THRESHOLD * 2 - 3),
+        (NTT_THRESHOLD * 2 + 1, NTT_THRESHOLD),
+        (NTT_THRESHOLD - 1, TOOM_3_THRESHOLD * 5),
+    ];
+
+    #[test]
+    fn mul_matches_schoolbook_across_thresholds() {
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        for &(a_len, b_len) in MUL_LENGTHS.iter() {
+            let a: Integer = random_integer(&mut seed, a_len);
+            let mut b: Integer = random_integer(&mut seed, b_len);
+            b.sign = a_len % 2 == 1;
+            let expected: Integer = schoolbook_mul(&a, &b);
+            assert_eq!(
+                Integer::mul(a.clone(), b.clone()),
+                expected,
+                "{} x {}",
+                a_len,
+                b_len
+            );
+            assert_eq!(Integer::mul(b, a), expected, "{} x {}", b_len, a_len);
+        }
+    }
+    #[test]
+    fn mul_of_all_ones_digits_across_thresholds() {
+        // every partial product and carry is at its largest
+        for &(a_len, b_len) in MUL_LENGTHS.iter() {
+            let a: Integer = Integer::from_digits(false, vec![Digit::MAX; a_len]);
+            let b: Integer = Integer::from_digits(false, vec![Digit::MAX; b_len]);
+            let expected: Integer = schoolbook_mul(&a, &b);
+            assert_eq!(
+                Integer::mul(a.clone(), b.clone()),
+                expected,
+                "{} x {}",
+                a_len,
+                b_len
+            );
+            // (2^n - 1)(2^m - 1) = 2^(n + m) - 2^n - 2^m + 1
+            let (n, m): (usize, usize) = (a_len * DIGIT_BITS as usize, b_len * DIGIT_BITS as usize);
+            let mut closed_form: Integer = Integer::shl(Integer::one(), n + m);
+            closed_form.sub_assign(&Integer::shl(Integer::one(), n));
+            closed_form.sub_assign(&Integer::shl(Integer::one(), m));
+            closed_form.add_assign(&Integer::one());
+            assert_eq!(expected, closed_form, "{} x {}", a_len, b_len);
+        }
+    }
+    #[test]
+    fn square_matches_schoolbook_across_thresholds() {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for len in [TOOM_3_THRESHOLD, NTT_THRESHOLD, NTT_THRESHOLD + 3] {
+            let a: Integer = random_integer(&mut seed, len);
+            assert_eq!(
+                Integer::mul(a.clone(), a.clone()),
+                schoolbook_mul(&a, &a),
+                "{}",
+                len
+            );
+        }
+    }
+
+    /* Division */
+
+    fn check_div_rem(quotient: &Integer, remainder: &Integer, divisor: &Integer) {
+        // builds the dividend from the quotient and remainder it must give back
+        let mut dividend: Integer = Integer::mul(quotient.clone(), divisor.clone());
+        dividend.add_assign(remainder);
+        let (q, r) = Integer::div_rem(dividend, divisor.clone()).unwrap();
+        assert_eq!(&q, quotient, "{} digit divisor", divisor.digits.len());
+        assert_eq!(&r, remainder, "{} digit divisor", divisor.digits.len());
+    }
+    #[test]
+    fn div_rem_across_division_threshold() {
+        let mut seed: u64 = 0x1234_5678_9abc_def1;
+        let lengths: [usize; 3] = [
+            DIVISION_THRESHOLD - 1,
+            DIVISION_THRESHOLD,
+            DIVISION_THRESHOLD + 1,
+        ];
+        for &divisor_len in lengths.iter() {
+            for quotient_len in lengths.into_iter().chain([divisor_len * 3 + 5]) {
+                let divisor: Integer = random_integer(&mut seed, divisor_len);
+                let quotient: Integer = random_integer(&mut seed, quotient_len);
+                // any remainder below the divisor, the largest one included
+                let remainder: Integer = Integer::sub(divisor.clone(), Integer::one());
+                check_div_rem(&quotient, &remainder, &divisor);
+                let remainder: Integer =
+                    Integer::rem(random_integer(&mut seed, divisor_len), divisor.clone()).unwrap();
+                check_div_rem(&quotient, &remainder, &divisor);
+            }
+        }
+    }
+    #[test]
+    fn div_rem_with_top_limb_set() {
+        // a normalized divisor needs no shift, with all ones every estimate is at its largest
+        let mut seed: u64 = 0x0bad_cafe_dead_beef;
+        for len in [
+            DIVISION_THRESHOLD - 1,
+            DIVISION_THRESHOLD,
+            DIVISION_THRESHOLD * 2 + 1,
+        ] {
+            let mut digits: Vec<Digit> = random_digits(&mut seed, len);
+            digits[len - 1] |= 1 << (DIGIT_BITS - 1);
+            let divisor: Integer = Integer::from_digits(false, digits);
+            let all_ones: Integer = Integer::from_digits(false, vec![Digit::MAX; len]);
+            let quotient: Integer = Integer::from_digits(false, vec![Digit::MAX; len * 2 + 3]);
+            let remainder: Integer = Integer::sub(divisor.clone(), Integer::one());
+            check_div_rem(&quotient, &remainder, &divisor);
+            check_div_rem(&quotient, &Integer::zero(), &all_ones);
+            check_div_rem(
+                &random_integer(&mut seed, len + DIVISION_THRESHOLD),
+                &remainder,
+                &divisor,
+            );
+        }
+    }
+    #[test]
+    fn div_rem_when_the_quotient_estimate_is_too_large() {
+        // q_hat from the top two digits is corrected by the second divisor digit
+        let dividend: Integer = Integer::from_digits(false, vec![0, 0xffff_fffe, 0x8000_0000]);
+        let divisor: Integer = Integer::from_digits(false, vec![0xffff_ffff, 0x8000_0000]);
+        let (quotient, remainder) = Integer::div_rem(dividend.clone(), divisor.clone()).unwrap();
+        assert_eq!(quotient, Integer::from(0xffff_ffffu64));
+        assert_eq!(
+            Integer::add(Integer::mul(quotient, divisor), remainder),
+            dividend
+        );
+        // and here only the add back step finds it one too large
+        let dividend: Integer = Integer::from_digits(false, vec![3, 0, 0x8000_0000]);
+        let divisor: Integer = Integer::from_digits(false, vec![1, 0, 0x2000_0000]);
+        let (quotient, remainder) = Integer::div_rem(dividend, divisor).unwrap();
+        assert_eq!(quotient, Integer::from(3));
+        assert_eq!(
+            remainder,
+            Integer::from_digits(false, vec![0, 0, 0x2000_0000])
+        );
+        // the same shapes repeated through a divisor long enough for the recursive division
+        let mut digits: Vec<Digit> = vec![0; DIVISION_THRESHOLD * 3];
+        digits.extend([0xffff_fffe, 0x8000_0000]);
+        let dividend: Integer = Integer::from_digits(false, digits);
+        let mut digits: Vec<Digit> = vec![0xffff_ffff; DIVISION_THRESHOLD];
+        digits.push(0x8000_0000);
+        let divisor: Integer = Integer::from_digits(false, digits);
+        let (quotient, remainder) = Integer::div_rem(dividend.clone(), divisor.clone()).unwrap();
+        assert!(!remainder.sign && remainder < divisor);
+        assert_eq!(
+            Integer::add(Integer::mul(quotient, divisor), remainder),
+            dividend
+        );
+    }
+
+    /* Primality */
+
+    #[test]
+    fn is_probable_prime_rejects_strong_pseudoprimes() {
+        // 3215031751 passes bases 2, 3, 5 and 7, 3825123056546413051 every base up to 37
+        assert!(!is_prime_u64(3_215_031_751));
+        assert!(!Integer::is_probable_prime(Integer::from(3_215_031_751u64)));
+        assert!(!Integer::is_probable_prime(Integer::from(
+            3_825_123_056_546_413_051u64
+        )));
+    }
+    #[test]
+    fn is_probable_prime_rejects_carmichael_numbers() {
+        for n in [
+            561u64, 1105, 1729, 2465, 2821, 6601, 8911, 41041, 825265, 321197185,
+        ] {
+            assert!(!Integer::is_probable_prime(Integer::from(n)), "{}", n);
+        }
+        // above 2^64, (6k + 1)(12k + 1)(18k + 1) for k = 350730 is also a strong
+        // pseudoprime to base 2, so only the lucas test can reject it
+        let carmichael: Integer = integer("55914458787065686681");
+        assert!(Integer::is_strong_probable_prime(
+            &carmichael,
+            Integer::from(2)
+        ));
+        assert!(!Integer::is_probable_prime(carmichael));
+        assert!(!Integer::is_probable_prime(integer("55943159843506316041")));
+    }
+    #[test]
+    fn is_probable_prime_just_above_u64() {
+        let two_64: Integer = Integer::shl(Integer::one(), 64);
+        assert!(Integer::is_probable_prime(Integer::from(
+            18_446_744_073_709_551_557u64
+        )));
+        // 2^64 + 1 = 274177 * 67280421310721, 2^64 + 13 is the next prime
+        assert!(!Integer::is_probable_prime(Integer::add(
+            two_64.clone(),
+            Integer::one()
+        )));
+        for offset in 2..13 {
+            let n: Integer = Integer::add(two_64.clone(), Integer::from(offset));
+            assert!(!Integer::is_probable_prime(n), "2^64 + {}", offset);
+        }
+        assert!(Integer::is_probable_prime(Integer::add(
+            two_64.clone(),
+            Integer::from(13)
+        )));
+        assert_eq!(Integer::next_prime(two_64), integer("18446744073709551629"));
+        // the square of a prime and a product of two primes above 2^64
+        let p: Integer = Integer::from(4_294_967_311u64);
+        assert!(!Integer::is_probable_prime(Integer::mul(p.clone(), p)));
+        assert!(!Integer::is_probable_prime(integer(
+            "340282366920938464385711811117245792737"
+        )));
+        let mersenne: Integer = Integer::sub(Integer::shl(Integer::one(), 127), Integer::one());
+        assert!(Integer::is_probable_prime(mersenne));
+    }
+
+    /* Factorization */
+
+    fn factors(n: &str) -> Vec<(String, u32)> {
+        Integer::factor(integer(n))
+            .into_iter()
+            .map(|(prime, exp)| (prime.to_string(), exp))
+            .collect()
+    }
+    #[test]
+    fn factor_small_values_and_signs() {
+        assert_eq!(factors("0"), []);
+        assert_eq!(factors("1"), []);
+        assert_eq!(factors("-1"), []);
+        assert_eq!(
+            factors("-360"),
+            [
+                ("2".to_string(), 3),
+                ("3".to_string(), 2),
+                ("5".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            factors("4294967297"),
+            [("641".to_string(), 1), ("6700417".to_string(), 1)]
+        );
+    }
+    #[test]
+    fn factor_repeated_large_primes() {
+        // (2^61 - 1)^3 * (10^9 + 7)^2 * 2^5 * 3
+        assert_eq!(
+            factors("1176956591862394754748849043203501930708143272074339201168653541076075507104"),
+            [
+                ("2".to_string(), 5),
+                ("3".to_string(), 1),
+                ("1000000007".to_string(), 2),
+                ("2305843009213693951".to_string(), 3),
+            ]
+        );
+        // 10000019^2 * 10000079, two primes past the trial division
+        assert_eq!(
+            factors("1000011700033630028519"),
+            [("10000019".to_string(), 2), ("10000079".to_string(), 1)]
+        );
+        // a prime power is split by its root
+        assert_eq!(
+            factors(
+                &Integer::pow(integer("1000000000039"), integer("4"))
+                    .unwrap()
+                    .to_string()
+            ),
+            [("1000000000039".to_string(), 4)]
+        );
+    }
+
+    /* Combinatorics */
+
+    #[test]
+    fn factorial_against_a_running_product() {
+        let mut product: Integer = Integer::one();
+        for n in 0..=300u32 {
+            if n > 0 {
+                product = Integer::mul(product, Integer::from(n));
+            }
+            assert_eq!(Integer::factorial(n), product, "{}!", n);
+        }
+        assert_eq!(Integer::factorial(20).to_string(), "2432902008176640000");
+    }
+    #[test]
+    fn binomial_and_multinomial() {
+        assert_eq!(
+            Integer::binomial(integer("50"), 25),
+            integer("126410606437752")
+        );
+        assert_eq!(Integer::binomial(integer("50"), 0), integer("1"));
+        assert_eq!(Integer::binomial(integer("10"), 11), integer("0"));
+        assert_eq!(Integer::binomial(integer("0"), 0), integer("1"));
+        // (-5 choose 3) = -(7 choose 3)
+        assert_eq!(Integer::binomial(integer("-5"), 3), integer("-35"));
+        assert_eq!(Integer::binomial(integer("-5"), 4), integer("70"));
+        assert_eq!(Integer::multinomial(&[2, 3, 4]), integer("1260"));
+        assert_eq!(Integer::multinomial(&[]), integer("1"));
+        assert_eq!(Integer::multinomial(&[0, 5]), integer("1"));
+    }
+    #[test]
+    fn fibonacci_and_lucas() {
+        assert_eq!(Integer::fibonacci(0), integer("0"));
+        assert_eq!(Integer::fibonacci(1), integer("1"));
+        assert_eq!(Integer::fibonacci(100), integer("354224848179261915075"));
+        assert_eq!(Integer::lucas(0), integer("2"));
+        assert_eq!(Integer::lucas(1), integer("1"));
+        assert_eq!(Integer::lucas(50), integer("28143753123"));
+        // L(n) = F(n - 1) + F(n + 1)
+        for n in 1..200u32 {
+            assert_eq!(
+                Integer::lucas(n),
+                Integer::add(Integer::fibonacci(n - 1), Integer::fibonacci(n + 1))
+            );
+        }
+    }
+
+    /* In-place arithmetic */
+
+    #[test]
+    fn in_place_integer_addition_and_subtraction() {
+        // every sign pair, with carries and borrows across limbs and results that cancel to zero
+        let values: [&str; 6] = [
+            "0",
+            "1",
+            "-1",
+            "4294967295",
+            "-4294967296",
+            "18446744073709551616",
+        ];
+        for a in values {
+            for b in values {
+                let expected_sum: i128 = a.parse::<i128>().unwrap() + b.parse::<i128>().unwrap();
+                let expected_difference: i128 =
+                    a.parse::<i128>().unwrap() - b.parse::<i128>().unwrap();
+                let mut sum: Integer = integer(a);
+                sum.add_assign(&integer(b));
+                assert_eq!(sum, Integer::from(expected_sum), "{} + {}", a, b);
+                let mut difference: Integer = integer(a);
+                difference.sub_assign(&integer(b));
+                assert_eq!(
+                    difference,
+                    Integer::from(expected_difference),
+                    "{} - {}",
+                    a,
+                    b
+                );
+            }
+        }
+        let mut n: Integer = integer("-5");
+        n.add_assign(&integer("5"));
+        assert!(n == Integer::zero() && !n.sign);
+    }
+    #[test]
+    fn mul_into_reuses_the_output() {
+        let mut seed: u64 = 16;
+        let mut out: Integer = random_integer(&mut seed, 70);
+        for (a_len, b_len) in [
+            (1, 1),
+            (3, 5),
+            (KARATSUBA_THRESHOLD + 4, KARATSUBA_THRESHOLD + 9),
+        ] {
+            let a: Integer = random_integer(&mut seed, a_len);
+            let b: Integer = Integer::opposite(random_integer(&mut seed, b_len));
+            Integer::mul_into(&a, &b, &mut out);
+            assert_eq!(out, Integer::mul(a.clone(), b.clone()));
+        }
+        // a zero product is never negative, whatever out held before
+        Integer::mul_into(&integer("-7"), &integer("0"), &mut out);
+        assert!(out == Integer::zero() && !out.sign);
+    }
+    #[test]
+    fn borrowed_comparisons() {
+        assert_eq!(
+            Integer::cmp_ref(&integer("-3"), &integer("2")),
+            Ordering::Less
+        );
+        assert_eq!(
+            Integer::cmp_ref(&integer("-3"), &integer("-20")),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Integer::cmp_ref(
+                &integer("18446744073709551616"),
+                &integer("18446744073709551615")
+            ),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Integer::cmp_ref(&integer("0"), &integer("-0")),
+            Ordering::Equal
+        );
+        assert_eq!(
+            Integer::cmp_abs(&integer("-3"), &integer("2")),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Integer::cmp_abs(&integer("-20"), &integer("20")),
+            Ordering::Equal
+        );
+    }
+    #[test]
+    fn in_place_float_addition_and_subtraction() {
+        let mut n: Float = float("1.25");
+        n.add_assign(&float("-0.005"));
+        assert_eq!(n.to_string(), "1.245");
+        n.sub_assign(&float("2e3"));
+        assert_eq!(n.to_string(), "-1998.755");
+        n.sub_assign(&float("-1998.755"));
+        assert_eq!(n, float("0"));
+    }
+    #[test]
+    fn addition_of_points_too_far_apart() {
+        // 10^-4999999995 sits more than u32::MAX digits below 3, the exact sum cannot be written
+        // out, while a zero on either side leaves the other operand as the exact sum
+        let tiny: Float = Float::mul(float("1e-999999999"), float("1e-999999999"));
+        let tiny: Float = Float::mul(Float::mul(tiny.clone(), tiny), float("1e-999999999"));
+        assert_eq!(
+            Float::add_exact(float("3"), tiny.clone()),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            Float::sub_exact(tiny.clone(), float("3")),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            Float::add_exact(tiny.clone(), float("0.00")),
+            Ok(tiny.clone())
+        );
+        assert_eq!(
+            Float::sub_exact(float("0"), tiny.clone()),
+            Ok(Float::opposite(tiny.clone()))
+        );
+        // under a context the tiny operand only decides the direction of rounding
+        let context = |rounding: RoundingMode| {
+            Context::new(
+                Precision::SignificantDigits(NonZeroUsize::new(5).unwrap()),
+                rounding,
+            )
+        };
+        let mut n: Float = float("3");
+        n.add_assign_context(&tiny, &context(RoundingMode::HalfEven));
+        assert_eq!(n, float("3"));
+        n.add_assign_context(&tiny, &context(RoundingMode::Ceiling));
+        assert_eq!(n, float("3.0001"));
+        n.sub_assign_context(&tiny, &context(RoundingMode::Truncate));
+        assert_eq!(n, float("3"));
+        let mut n: Float = tiny.clone();
+        n.sub_assign_context(&float("3"), &context(RoundingMode::Ceiling));
+        assert_eq!(n, float("-2.9999"));
+    }
+    #[test]
+    fn addition_across_an_extreme_exponent_gap() {
+        // 10^2000000000 + 1 would take two billion digits exactly, under a context it takes a few
+        let huge: Float = Float::new(Integer::from(-2_000_000_000), Integer::one());
+        let context = |rounding: RoundingMode| {
+            Context::new(
+                Precision::SignificantDigits(NonZeroUsize::new(10).unwrap()),
+                rounding,
+            )
+        };
+        assert_eq!(
+            Float::add_context(huge.clone(), float("1"), &context(RoundingMode::HalfEven)),
+            huge
+        );
+        assert_eq!(
+            Float::add_context(
+                float("1"),
+                huge.clone(),
+                &context(RoundingMode::AwayFromZero)
+            ),
+            Float::new(Integer::from(-1_999_999_991), Integer::from(1_000_000_001))
+        );
+        assert_eq!(
+            Float::sub_context(huge.clone(), float("1"), &context(RoundingMode::Floor)),
+            Float::new(
+                Integer::from(-1_999_999_990),
+                Integer::from(9_999_999_999_u64)
+            )
+        );
+        assert_eq!(
+            Float::sub_context(float("-1"), huge.clone(), &context(RoundingMode::Ceiling)),
+            Float::opposite(huge.clone())
+        );
+        // the same gap in fixed decimal places
+        let places: Context = Context::new(
+            Precision::DecimalPlaces(-1_999_999_995),
+            RoundingMode::Ceiling,
+        );
+        assert_eq!(
+            Float::add_context(huge.clone(), float("1e-30"), &places),
+            Float::add_exact(
+                huge,
+                Float::new(Integer::from(-1_999_999_995), Integer::one())
+            )
+            .unwrap()
+        );
+    }
+
+    /* ModInt */
+
+    fn check_mod_arithmetic(modulus: Integer, seed: &mut u64) {
+        // every operation against the same one on Integer followed by a reduction
+        let context: Rc<ModContext> = ModContext::new(modulus.clone()).unwrap();
+        let reduce = |n: Integer| Integer::mod_floor(n, modulus.clone()).unwrap();
+        let limbs: usize = modulus.digits.len() + 1;
+        let mut values: Vec<Integer> = vec![
+            Integer::zero(),
+            Integer::one(),
+            Integer::from(-1),
+            Integer::sub(modulus.clone(), Integer::one()),
+            modulus.clone(),
+        ];
+        for sign in [false, true] {
+            let mut value: Integer = random_integer(seed, limbs);
+            value.sign = sign;
+            values.push(value);
+        }
+        for a in values.iter() {
+            let x: ModInt = ModInt::new(a.clone(), &context);
+            assert_eq!(x.value(), reduce(a.clone()), "{} mod {}", a, modulus);
+            for b in values.iter() {
+                let y: ModInt = ModInt::new(b.clone(), &context);
+                let sum: ModInt = ModInt::add(x.clone(), y.clone());
+                assert_eq!(sum.value(), reduce(Integer::add(a.clone(), b.clone())));
+                let difference: ModInt = ModInt::sub(x.clone(), y.clone());
+                assert_eq!(
+                    difference.value(),
+                    reduce(Integer::sub(a.clone(), b.clone()))
+                );
+                let product: ModInt = ModInt::mul(x.clone(), y.clone());
+                assert_eq!(product.value(), reduce(Integer::mul(a.clone(), b.clone())));
+            }
+            let exp: Integer = Integer::from(65_537);
+            assert_eq!(
+                ModInt::pow(x.clone(), exp.clone()).unwrap().value(),
+                Integer::pow_mod(a.clone(), exp, modulus.clone()).unwrap()
+            );
+            match Integer::mod_inverse(a.clone(), modulus.clone()) {
+                Ok(inverse) => assert_eq!(ModInt::inverse(x).unwrap().value(), inverse),
+                Err(error) => assert_eq!(ModInt::inverse(x).unwrap_err(), error),
+            }
+        }
+    }
+    #[test]
+    fn mod_int_with_even_modulus() {
+        let mut seed: u64 = 0x5851_f42d_4c95_7f2d;
+        for modulus in [
+            Integer::from(1_000),
+            Integer::shl(Integer::one(), 100),
+            Integer::mul(Integer::from(6), random_integer(&mut seed, 3)),
+        ] {
+            assert!(ModContext::new(modulus.clone())
+                .unwrap()
+                .montgomery
+                .is_none());
+            check_mod_arithmetic(modulus, &mut seed);
+        }
+    }
+    #[test]
+    fn mod_int_with_modulus_one() {
+        let mut seed: u64 = 0x1405_7b7e_f767_814f;
+        let context: Rc<ModContext> = ModContext::new(Integer::one()).unwrap();
+        assert!(context.montgomery.is_none());
+        assert_eq!(ModInt::one(&context), ModInt::zero(&context));
+        assert_eq!(
+            ModInt::new(Integer::from(-7), &context).value(),
+            Integer::zero()
+        );
+        // every residue is 0 and so a unit
+        assert_eq!(
+            ModInt::inverse(ModInt::zero(&context)).unwrap(),
+            ModInt::zero(&context)
+        );
+        check_mod_arithmetic(Integer::one(), &mut seed);
+        assert!(ModContext::new(Integer::zero()).is_err());
+    }
+    #[test]
+    fn mod_int_with_largest_limb_modulus() {
+        // m_prime and every montgomery carry at their largest
+        let mut seed: u64 = 0x2127_599b_f432_5c37;
+        for modulus in [
+            Integer::from(Digit::MAX),
+            Integer::from(Digit::MAX - 4),
+            Integer::from_digits(false, vec![Digit::MAX; 4]),
+        ] {
+            assert!(ModContext::new(modulus.clone())
+                .unwrap()
+                .montgomery
+                .is_some());
+            check_mod_arithmetic(modulus, &mut seed);
+        }
+    }
+    #[test]
+    fn mod_int_inverse_of_a_non_unit() {
+        for modulus in [12, 15, Digit::MAX as i64] {
+            let context: Rc<ModContext> = ModContext::new(Integer::from(modulus)).unwrap();
+            let non_unit: ModInt = ModInt::new(Integer::from(modulus / 3 * 2), &context);
+            assert_eq!(
+                ModInt::inverse(non_unit.clone()),
+                Err(ArithmeticError::NotInvertible)
+            );
+            assert_eq!(
+                ModInt::div(ModInt::one(&context), non_unit.clone()),
+                Err(ArithmeticError::NotInvertible)
+            );
+            assert_eq!(
+                ModInt::pow(non_unit, Integer::from(-2)),
+                Err(ArithmeticError::NotInvertible)
+            );
+            assert_eq!(
+                ModInt::inverse(ModInt::zero(&context)),
+                Err(ArithmeticError::NotInvertible)
+            );
+        }
+    }
+
+    /* Canonical form */
+
+    #[test]
+    fn results_are_canonical() {
+        // a negative zero out of any path is caught by validate in debug builds, and checked here
+        let zeros: [Integer; 6] = [
+            integer("-0"),
+            Integer::sub(integer("4294967296"), integer("4294967296")),
+            Integer::add(
+                integer("-18446744073709551616"),
+                integer("18446744073709551616"),
+            ),
+            Integer::mul(integer("-5"), integer("0")),
+            Integer::rem(integer("-6"), integer("3")).unwrap(),
+            Integer::div(integer("-1"), integer("2")).unwrap(),
+        ];
+        for zero in zeros {
+            zero.validate();
+            assert!(!zero.sign && zero.digits == [0]);
+        }
+        // a borrow out of the top limb leaves no leading zero limbs
+        let n: Integer = Integer::sub(integer("18446744073709551616"), integer("1"));
+        assert_eq!(n.digits, [u32::MAX, u32::MAX]);
+        // Integer::new takes decimal digits lowest first, high zeros included
+        assert_eq!(Integer::new(false, vec![7, 0, 0]), integer("7"));
+        assert_eq!(Integer::new(true, vec![0, 0]), integer("0"));
+        // a zero result drops the scale of its operands, whatever the operation
+        assert_eq!(Float::sub(float("1.5"), float("1.50")).to_string(), "0");
+        for (a, b) in [
+            ("1.5", "1.50"),
+            ("-2.25e-40", "-2.2500e-40"),
+            ("7e30", "7000e27"),
+        ] {
+            let difference: Float = Float::sub(float(a), float(b));
+            assert!(
+                difference.is_zero() && difference.point.is_zero(),
+                "{} - {}",
+                a,
+                b
+            );
+            let mut n: Float = float(a);
+            n.sub_assign(&float(b));
+            assert!(n.point.is_zero(), "{} - {}", a, b);
+        }
+        assert!(float("0.000").point.is_zero() && float("-0e5").point.is_zero());
+        assert!(Float::mul(float("0.00"), float("1.5")).point.is_zero());
+        assert!(!Float::mul(float("-0.5"), float("0")).value.sign);
+    }
+    #[test]
+    fn helpers_on_the_canonical_form() {
+        assert!(integer("0").is_zero() && integer("-0").is_zero());
+        assert!(
+            integer("1").is_one() && !integer("-1").is_one() && !integer("4294967297").is_one()
+        );
+        assert!(integer("0").is_even() && integer("-4294967296").is_even());
+        assert!(!integer("-4294967297").is_even());
+        assert_eq!(integer("-123456789012345678901").signum(), integer("-1"));
+        assert_eq!(integer("-0").signum(), integer("0"));
+        assert_eq!(integer("7").signum(), integer("1"));
+        assert_eq!(
+            Integer::abs(integer("-18446744073709551616")),
+            integer("18446744073709551616")
+        );
+        assert_eq!(float("-0.25").signum(), float("-1"));
+        assert_eq!(float("0.000").signum(), float("0"));
+        assert_eq!(Float::abs(float("-2.50")).to_string(), "2.50");
+        assert!(float("-0.000").is_zero());
+    }
+
+    /* Float division */
+
+    fn digits(count: usize) -> Precision {
+        Precision::SignificantDigits(NonZeroUsize::new(count).unwrap())
+    }
+    #[test]
+    fn division_to_significant_digits() {
+        let divide = |a: &str, b: &str, count: usize| Float::div(float(a), float(b), digits(count));
+        assert_eq!(divide("1", "3", 5).unwrap().to_string(), "0.33333");
+        assert_eq!(divide("-2", "3", 5).unwrap().to_string(), "-0.66667");
+        assert_eq!(divide("2", "-3e-20", 3).unwrap(), float("-6.67e19"));
+        // 9.9995 / 1 carries into a new digit, 0.125 / 1 ties to even
+        assert_eq!(divide("9.9995", "1", 4).unwrap().to_string(), "10.00");
+        assert_eq!(divide("0.125", "1", 2).unwrap().to_string(), "0.12");
+        assert_eq!(divide("0.135", "1", 2).unwrap().to_string(), "0.14");
+        assert_eq!(divide("0", "7", 3).unwrap(), float("0"));
+        assert_eq!(
+            divide("1", "7", 30).unwrap().to_string(),
+            "0.142857142857142857142857142857"
+        );
+    }
+    #[test]
+    fn division_to_decimal_places() {
+        let divide = |a: &str, b: &str, places: isize| {
+            Float::div(float(a), float(b), Precision::DecimalPlaces(places))
+        };
+        assert_eq!(divide("1", "3", 4).unwrap().to_string(), "0.3333");
+        assert_eq!(divide("-1", "8", 2).unwrap().to_string(), "-0.12");
+        assert_eq!(divide("1", "8", 0).unwrap().to_string(), "0");
+        assert_eq!(divide("12345", "1", -2).unwrap(), float("12300"));
+        assert_eq!(divide("12350", "1", -2).unwrap(), float("12400"));
+        // a quotient far below the last place rounds to zero, which has no scale
+        assert_eq!(divide("1", "3e40", 5).unwrap().to_string(), "0");
+        assert_eq!(divide("0", "-3", 2).unwrap().to_string(), "0");
+        // a scale that cannot be written out is an error rather than a panic
+        assert_eq!(
+            divide("1", "0.3", isize::MAX),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            divide("1", "3", 5_000_000_000),
+            Err(ArithmeticError::Overflow)
+        );
+        // while a quotient far below the last place still rounds to zero
+        assert_eq!(divide("1", "3", isize::MIN).unwrap(), float("0"));
+    }
+    #[test]
+    fn division_under_each_rounding_mode() {
+        // -2/3 = -0.666.. and 1/8 = 0.125, an inexact quotient and a tie
+        let cases: [(RoundingMode, &str, &str); 6] = [
+            (RoundingMode::HalfEven, "-0.67", "0.12"),
+            (RoundingMode::HalfUp, "-0.67", "0.13"),
+            (RoundingMode::Floor, "-0.67", "0.12"),
+            (RoundingMode::Ceiling, "-0.66", "0.13"),
+            (RoundingMode::Truncate, "-0.66", "0.12"),
+            (RoundingMode::AwayFromZero, "-0.67", "0.13"),
+        ];
+        for (rounding, thirds, eighth) in cases {
+            let context: Context = Context::new(Precision::DecimalPlaces(2), rounding);
+            assert_eq!(
+                Float::div_context(float("-2"), float("3"), &context)
+                    .unwrap()
+                    .to_string(),
+                thirds
+            );
+            assert_eq!(
+                Float::div_context(float("1"), float("8"), &context)
+                    .unwrap()
+                    .to_string(),
+                eighth
+            );
+        }
+    }
+    #[test]
+    fn division_by_zero() {
+        for precision in [digits(5), Precision::DecimalPlaces(2)] {
+            assert_eq!(
+                Float::div(float("1"), float("0"), precision),
+                Err(ArithmeticError::DivisionByZero)
+            );
+            assert_eq!(
+                Float::div(float("0"), float("-0.00"), precision),
+                Err(ArithmeticError::DivisionByZero)
+            );
+        }
+    }
+
+    /* Rounding */
+
+    const ROUNDING_MODES: [RoundingMode; 6] = [
+        RoundingMode::HalfEven,
+        RoundingMode::HalfUp,
+        RoundingMode::Floor,
+        RoundingMode::Ceiling,
+        RoundingMode::Truncate,
+        RoundingMode::AwayFromZero,
+    ];
+    #[test]
+    fn round_to_under_each_mode() {
+        // one expected result per mode, in the order of ROUNDING_MODES
+        let cases: [(&str, isize, [&str; 6]); 8] = [
+            ("2.5", 0, ["2", "3", "2", "3", "2", "3"]),
+            ("3.5", 0, ["4", "4", "3", "4", "3", "4"]),
+            ("-2.5", 0, ["-2", "-3", "-3", "-2", "-2", "-3"]),
+            ("-2.51", 0, ["-3", "-3", "-3", "-2", "-2", "-3"]),
+            ("1.234", 2, ["1.23", "1.23", "1.23", "1.24", "1.23", "1.24"]),
+            (
+                "-1.235",
+                2,
+                ["-1.24", "-1.24", "-1.24", "-1.23", "-1.23", "-1.24"],
+            ),
+            (
+                "99.96",
+                1,
+                ["100.0", "100.0", "99.9", "100.0", "99.9", "100.0"],
+            ),
+            ("-0.004", 2, ["0", "0", "-0.01", "0", "0", "-0.01"]),
+        ];
+        for (value, places, expected) in cases {
+            for (rounding, expected) in ROUNDING_MODES.into_iter().zip(expected) {
+                let rounded: Float = Float::round_to(float(value), places, rounding).unwrap();
+                assert_eq!(
+                    rounded.to_string(),
+                    expected,
+                    "{} to {} places, {:?}",
+                    value,
+                    places,
+                    rounding
+                );
+            }
+        }
+    }
+    #[test]
+    fn round_to_scales_and_limits() {
+        let round = |value: &str, places: isize| {
+            Float::round_to(float(value), places, RoundingMode::HalfEven)
+        };
+        assert_eq!(round("1.5", 3).unwrap().to_string(), "1.500");
+        assert_eq!(round("1250", -2).unwrap(), float("1200"));
+        assert_eq!(round("0.0005", 2).unwrap().to_string(), "0");
+        assert_eq!(round("-7e-999999999", 3).unwrap().to_string(), "0");
+        // appending more than u32::MAX zeros is an error rather than a panic or a huge value
+        assert_eq!(
+            round("1e-999999999", isize::MAX),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(round("0", isize::MAX).unwrap(), float("0"));
+        assert_eq!(Float::trunc(float("-99.99")), float("-99"));
+        assert_eq!(Float::trunc(float("1e999999999")), float("1e999999999"));
+    }
+    #[test]
+    fn round_sig_under_each_mode() {
+        let cases: [(&str, usize, [&str; 6]); 5] = [
+            ("12.5", 2, ["12", "13", "12", "13", "12", "13"]),
+            ("-12.5", 2, ["-12", "-13", "-13", "-12", "-12", "-13"]),
+            ("99.96", 3, ["100", "100", "99.9", "100", "99.9", "100"]),
+            (
+                "-0.00099951",
+                3,
+                [
+                    "-0.00100",
+                    "-0.00100",
+                    "-0.00100",
+                    "-0.000999",
+                    "-0.000999",
+                    "-0.00100",
+                ],
+            ),
+            ("1.5", 4, ["1.5", "1.5", "1.5", "1.5", "1.5", "1.5"]),
+        ];
+        for (value, count, expected) in cases {
+            for (rounding, expected) in ROUNDING_MODES.into_iter().zip(expected) {
+                let rounded: Float =
+                    Float::round_sig(float(value), NonZeroUsize::new(count).unwrap(), rounding);
+                assert_eq!(
+                    rounded.to_string(),
+                    expected,
+                    "{} to {} digits, {:?}",
+                    value,
+                    count,
+                    rounding
+                );
+            }
+        }
+    }
+    #[test]
+    fn context_rounds_every_operation() {
+        let context: Context = Context::new(Precision::DecimalPlaces(2), RoundingMode::HalfUp);
+        assert_eq!(
+            context.add(float("0.125"), float("0.5")).to_string(),
+            "0.63"
+        );
+        assert_eq!(
+            context.sub(float("0.125"), float("0.5")).to_string(),
+            "-0.38"
+        );
+        assert_eq!(
+            context.mul(float("1.05"), float("1.05")).to_string(),
+            "1.10"
+        );
+        assert_eq!(
+            context.div(float("2"), float("3")).unwrap().to_string(),
+            "0.67"
+        );
+        // the far smaller operand still decides a directed rounding
+        let ceiling: Context = Context::new(Precision::DecimalPlaces(0), RoundingMode::Ceiling);
+        assert_eq!(ceiling.add(float("5"), float("1e-999999999")), float("6"));
+        assert_eq!(ceiling.sub(float("5"), float("1e-999999999")), float("5"));
+    }
+
+    #[test]
+    fn plain_arithmetic_rounds_to_the_current_context() {
+        // 1.1^200 has 209 digits exactly, every plain product is rounded to 34 of them
+        assert_eq!(Context::current(), Context::default());
+        let mut power: Float = float("1");
+        let mut explicit: Float = float("1");
+        let mut exact: Float = float("1");
+        for _ in 0..200 {
+            power = Float::mul(power, float("1.1"));
+            assert!(power.value.decimal_length() <= 34);
+            explicit = Context::default().mul(explicit, float("1.1"));
+            exact = Float::mul_exact(exact, float("1.1"));
+        }
+        assert_eq!(power, explicit);
+        assert_eq!(power, float("189905276.4604618242121820463954116"));
+        assert_eq!(exact.value.decimal_length(), 209);
+        let thirty_digits: Context = Context::new(
+            Precision::SignificantDigits(NonZeroUsize::new(30).unwrap()),
+            RoundingMode::HalfEven,
+        );
+        assert_eq!(thirty_digits.round(power), thirty_digits.round(exact));
+        // a scope sets the precision of the operators too, and the sum of 0.1 ten times is exact
+        let three_digits: Context = Context::new(
+            Precision::SignificantDigits(NonZeroUsize::new(3).unwrap()),
+            RoundingMode::HalfEven,
+        );
+        three_digits.scope(|| {
+            let mut power: Float = float("1");
+            for _ in 0..50 {
+                power *= float("1.1");
+                assert!(power.value.decimal_length() <= 3);
+            }
+            assert_eq!(power, float("117"));
+            let mut sum: Float = float("0");
+            for _ in 0..10 {
+                sum += float("0.1");
+            }
+            assert_eq!(sum, float("1"));
+        });
+        // fixed decimal places keep the scale of every result
+        let cents: Context = Context::new(Precision::DecimalPlaces(2), RoundingMode::HalfUp);
+        cents.scope(|| {
+            assert_eq!((float("19.99") * float("0.0725")).to_string(), "1.45");
+            assert_eq!((float("10") - float("0.005")).to_string(), "10.00");
+        });
+        assert_eq!(Context::current(), Context::default());
+    }
+    #[test]
+    fn context_scope_is_restored_after_a_panic() {
+        let previous: Context = Context::set_current(Context::new(
+            Precision::DecimalPlaces(0),
+            RoundingMode::Floor,
+        ));
+        let inner: Context = Context::new(Precision::DecimalPlaces(5), RoundingMode::Ceiling);
+        let result = std::panic::catch_unwind(|| inner.scope(|| panic!("inside the scope")));
+        assert!(result.is_err());
+        assert_eq!(Float::add(float("1.9"), float("0")), float("1"));
+        Context::set_current(previous);
+        assert_eq!(Context::current(), Context::default());
+    }
+
+    /* f64 conversion */
+
+    fn exact(number: f64) -> Float {
+        Float::from_f64(number).unwrap()
+    }
+    #[test]
+    fn from_f64_is_exact() {
+        assert_eq!(
+            exact(0.1).to_string(),
+            "0.1000000000000000055511151231257827021181583404541015625"
+        );
+        assert_eq!(exact(-2.5).to_string(), "-2.5");
+        assert_eq!(exact(1e22), float("1e22"));
+        assert_eq!(
+            exact(f64::from_bits(1)),
+            Float::mul_exact(exact(2f64.powi(-537)), exact(2f64.powi(-537)))
+        );
+        assert_eq!(
+            Float::try_from(0.1f32).unwrap().to_string(),
+            "0.100000001490116119384765625"
+        );
+        assert_eq!(Float::from_f64_shortest(0.1).unwrap().to_string(), "0.1");
+        assert_eq!(
+            Float::from_f64_shortest(-1.5e-300).unwrap(),
+            float("-1.5e-300")
+        );
+        for number in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(Float::from_f64(number), Err(TryFromFloatError));
+            assert_eq!(Float::from_f64_shortest(number), Err(TryFromFloatError));
+        }
+    }
+    #[test]
+    fn to_f64_rounds_half_to_even() {
+        // 2^53 + 1 and 2^53 + 3 sit halfway between two f64 values
+        assert_eq!(
+            Float::from(9007199254740993u64).to_f64(),
+            9007199254740992.0
+        );
+        assert_eq!(
+            Float::from(9007199254740995u64).to_f64(),
+            9007199254740996.0
+        );
+        assert_eq!(
+            float("9007199254740993.000000000000000000001").to_f64(),
+            9007199254740994.0
+        );
+        assert_eq!(float("0.1").to_f64(), 0.1);
+        assert_eq!(float("-123456.789e-30").to_f64(), -123456.789e-30);
+        for number in [
+            1.0,
+            0.3,
+            -7.25e-200,
+            1.7976931348623157e308,
+            f64::MIN_POSITIVE,
+        ] {
+            assert_eq!(exact(number).to_f64(), number);
+        }
+    }
+    #[test]
+    fn to_f64_overflows_to_infinity() {
+        // f64::MAX + 2^970 is halfway to 2^1024 and ties to the even side, which overflows
+        let halfway: Float = Float::add_exact(exact(f64::MAX), exact(2f64.powi(970))).unwrap();
+        assert_eq!(halfway.clone().to_f64(), f64::INFINITY);
+        assert_eq!(
+            Float::sub_exact(halfway, float("1e-400")).unwrap().to_f64(),
+            f64::MAX
+        );
+        let two_power_1024: Float = Float::from(Integer::shl(Integer::one(), 1024));
+        assert_eq!(Float::opposite(two_power_1024).to_f64(), f64::NEG_INFINITY);
+        assert_eq!(float("1e999999999").to_f64(), f64::INFINITY);
+    }
+    #[test]
+    fn to_f64_subnormals_and_underflow() {
+        let smallest: Float = exact(f64::from_bits(1));
+        let scaled = |factor: &str| Float::mul_exact(smallest.clone(), float(factor)).to_f64();
+        // half the smallest subnormal ties to zero, anything more rounds up to it
+        assert_eq!(scaled("0.5"), 0.0);
+        assert_eq!(scaled("0.5000000000000000001"), f64::from_bits(1));
+        assert_eq!(scaled("0.75"), f64::from_bits(1));
+        assert_eq!(scaled("1.5"), f64::from_bits(2));
+        assert_eq!(scaled("2.5"), f64::from_bits(2));
+        let negative_zero: f64 = scaled("-0.5");
+        assert!(negative_zero == 0.0 && negative_zero.is_sign_negative());
+        // halfway below f64::MIN_POSITIVE ties to it, its mantissa is the even one
+        let below_normal: Float = Float::sub_exact(
+            exact(f64::MIN_POSITIVE),
+            Float::mul_exact(smallest, float("0.5")),
+        )
+        .unwrap();
+        assert_eq!(below_normal.to_f64(), f64::MIN_POSITIVE);
+        assert_eq!(float("2.4703282292062328e-324").to_f64(), f64::from_bits(1));
+        assert_eq!(
+            float("-1e-999999999").to_f64().to_bits(),
+            (-0.0f64).to_bits()
+        );
+    }
+
+    /* Formatting */
+
+    fn formatted(value: &str, format: FloatFormat) -> String {
+        float(value).format(&format)
+    }
+    #[test]
+    fn fixed_notation() {
+        let fixed: FloatFormat = FloatFormat::new(Notation::Fixed);
+        assert_eq!(formatted("-0.050", fixed), "-0.050");
+        assert_eq!(formatted("12e3", fixed), "12000");
+        assert_eq!(
+            formatted(
+                "1234567.891",
+                fixed.with_precision(Precision::DecimalPlaces(2))
+            ),
+            "1234567.89"
+        );
+        assert_eq!(
+            formatted("1.5", fixed.with_precision(Precision::DecimalPlaces(3))),
+            "1.500"
+        );
+        assert_eq!(formatted("1.5", fixed.with_precision(digits(4))), "1.500");
+        assert_eq!(formatted("98765", fixed.with_precision(digits(2))), "99000");
+        assert_eq!(formatted("0", fixed.with_precision(digits(3))), "0.00");
+        let ceiling: FloatFormat = fixed
+            .with_precision(Precision::DecimalPlaces(0))
+            .with_rounding(RoundingMode::Ceiling);
+        assert_eq!(formatted("2.1", ceiling), "3");
+        assert_eq!(formatted("-2.9", ceiling), "-2");
+    }
+    #[test]
+    fn scientific_notation() {
+        let scientific: FloatFormat = FloatFormat::new(Notation::Scientific);
+        assert_eq!(formatted("0.000123456", scientific), "1.23456e-4");
+        assert_eq!(
+            formatted("0.000123456", scientific.with_precision(digits(3))),
+            "1.23e-4"
+        );
+        assert_eq!(
+            formatted("-98765", scientific.with_precision(digits(2))),
+            "-9.9e4"
+        );
+        assert_eq!(
+            formatted("9.96", scientific.with_precision(digits(2))),
+            "1.0e1"
+        );
+        // the scale is kept as fraction digits
+        assert_eq!(formatted("100", scientific), "1.00e2");
+        assert_eq!(formatted("1e-999999999", scientific), "1e-999999999");
+        assert_eq!(format!("{:e}", float("12.50")), "1.250e1");
+        assert_eq!(format!("{:.1E}", float("-12345")), "-1.2E4");
+        assert_eq!(format!("{:>10.2e}", float("1.5")), "    1.50e0");
+    }
+    #[test]
+    fn engineering_notation() {
+        let engineering: FloatFormat = FloatFormat::new(Notation::Engineering);
+        assert_eq!(formatted("12345", engineering), "12.345e3");
+        assert_eq!(formatted("0.00012", engineering), "120e-6");
+        assert_eq!(formatted("-1.5", engineering), "-1.5e0");
+        assert_eq!(
+            formatted("123456789", engineering.with_precision(digits(2))),
+            "120e6"
+        );
+        assert_eq!(formatted("1e-999999999", engineering), "1e-999999999");
+        assert_eq!(formatted("1e-999999998", engineering), "10e-999999999");
+    }
+    #[test]
+    fn zeros_are_padded_only_to_a_precision() {
+        // zero has no scale of its own, a requested precision still pads it
+        assert_eq!(format!("{:.2e}", float("0.000")), "0.00e0");
+        assert_eq!(format!("{:.2e}", float("-0")), "0.00e0");
+        assert_eq!(format!("{:e}", float("0.000")), "0e0");
+        assert_eq!(
+            formatted("0.000", FloatFormat::new(Notation::Engineering)),
+            "0e0"
+        );
+        assert_eq!(formatted("0e5", FloatFormat::new(Notation::Fixed)), "0");
+        assert_eq!(
+            formatted("0e-999999999", FloatFormat::new(Notation::Fixed)),
+            "0"
+        );
+    }
+    #[test]
+    fn trimming_and_grouping() {
+        let fixed: FloatFormat = FloatFormat::new(Notation::Fixed);
+        assert_eq!(formatted("2.500", fixed.with_trim_zeros(true)), "2.5");
+        assert_eq!(formatted("3.000", fixed.with_trim_zeros(true)), "3");
+        assert_eq!(formatted("300", fixed.with_trim_zeros(true)), "300");
+        assert_eq!(
+            formatted(
+                "100",
+                FloatFormat::new(Notation::Scientific).with_trim_zeros(true)
+            ),
+            "1e2"
+        );
+        assert_eq!(
+            formatted("1234567.891", fixed.with_grouping(',')),
+            "1,234,567.891"
+        );
+        assert_eq!(formatted("-1234.5", fixed.with_grouping('_')), "-1_234.5");
+        assert_eq!(formatted("999", fixed.with_grouping(',')), "999");
+        assert_eq!(
+            formatted(
+                "123456",
+                FloatFormat::new(Notation::Engineering).with_grouping(',')
+            ),
+            "123.456e3"
+        );
+        let both: FloatFormat = fixed
+            .with_precision(Precision::DecimalPlaces(4))
+            .with_trim_zeros(true)
+            .with_grouping(' ');
+        assert_eq!(formatted("9999999.99999", both), "10 000 000");
+    }
+    #[test]
+    fn long_runs_of_zeros_are_written_as_an_exponent() {
+        // FORMAT_MAX_ZEROS zeros are still written out, one more switches to scientific
+        assert_eq!(
+            float("1e4096").to_string(),
+            format!("1{}", "0".repeat(FORMAT_MAX_ZEROS))
+        );
+        assert_eq!(float("1e4097").to_string(), "1e4097");
+        assert_eq!(
+            float("1e-4096").to_string(),
+            format!("0.{}1", "0".repeat(FORMAT_MAX_ZEROS - 1))
+        );
+        assert_eq!(float("-1e-4097").to_string(), "-1e-4097");
+        assert_eq!(float("1e999999999").to_string(), "1e999999999");
+        assert_eq!(format!("{:>12}", float("1e999999999")), " 1e999999999");
+    }
+
+    /* Float roots */
+
+    fn significant(digits: usize, rounding: RoundingMode) -> Context {
+        Context::new(
+            Precision::SignificantDigits(NonZeroUsize::new(digits).unwrap()),
+            rounding,
+        )
+    }
+    fn roots_under_each_mode(root: impl Fn(RoundingMode) -> Float, expected: [&str; 6]) {
+        for (rounding, expected) in ROUNDING_MODES.into_iter().zip(expected) {
+            assert_eq!(root(rounding).to_string(), expected, "{:?}", rounding);
+        }
+    }
+    #[test]
+    fn inexact_roots_under_each_mode() {
+        // sqrt 2 = 1.414213562 373.., cbrt -2 = -1.2599 2104.., sqrt 99.99 = 9.99949998..
+        roots_under_each_mode(
+            |rounding| Float::sqrt(float("2"), &significant(10, rounding)).unwrap(),
+            [
+                "1.414213562",
+                "1.414213562",
+                "1.414213562",
+                "1.414213563",
+                "1.414213562",
+                "1.414213563",
+            ],
+        );
+        roots_under_each_mode(
+            |rounding| Float::cbrt(float("-2"), &significant(5, rounding)).unwrap(),
+            [
+                "-1.2599", "-1.2599", "-1.2600", "-1.2599", "-1.2599", "-1.2600",
+            ],
+        );
+        roots_under_each_mode(
+            |rounding| Float::sqrt(float("99.99"), &significant(3, rounding)).unwrap(),
+            ["10.0", "10.0", "9.99", "10.0", "9.99", "10.0"],
+        );
+        roots_under_each_mode(
+            |rounding| {
+                let places: Context = Context::new(Precision::DecimalPlaces(3), rounding);
+                Float::sqrt(float("1e-10"), &places).unwrap()
+            },
+            ["0", "0", "0", "0.001", "0", "0.001"],
+        );
+        let context: Context = significant(20, RoundingMode::HalfEven);
+        assert_eq!(
+            Float::nth_root(float("2"), 7, &context)
+                .unwrap()
+                .to_string(),
+            "1.1040895136738123376"
+        );
+        assert_eq!(
+            Float::cbrt(float("3e-30"), &context).unwrap(),
+            float("1.4422495703074083823e-10")
+        );
+    }
+    #[test]
+    fn exact_roots_and_ties() {
+        // an exact root is the same under every mode, 0.25 to one digit is a tie
+        for rounding in ROUNDING_MODES {
+            assert_eq!(
+                Float::sqrt(float("0.0625"), &significant(5, rounding)),
+                Ok(float("0.25"))
+            );
+            assert_eq!(
+                Float::nth_root(float("-32"), 5, &significant(3, rounding)),
+                Ok(float("-2"))
+            );
+            assert_eq!(
+                Float::cbrt(float("0"), &significant(3, rounding)),
+                Ok(float("0"))
+            );
+        }
+        roots_under_each_mode(
+            |rounding| Float::sqrt(float("0.0625"), &significant(1, rounding)).unwrap(),
+            ["0.2", "0.3", "0.2", "0.3", "0.2", "0.3"],
+        );
+        roots_under_each_mode(
+            |rounding| Float::cbrt(float("-0.015625"), &significant(1, rounding)).unwrap(),
+            ["-0.2", "-0.3", "-0.3", "-0.2", "-0.2", "-0.3"],
+        );
+    }
+    #[test]
+    fn root_errors() {
+        let context: Context = significant(10, RoundingMode::HalfEven);
+        assert_eq!(
+            Float::sqrt(float("-0.01"), &context),
+            Err(ArithmeticError::NegativeRoot)
+        );
+        assert_eq!(
+            Float::nth_root(float("-8"), 4, &context),
+            Err(ArithmeticError::NegativeRoot)
+        );
+        assert_eq!(
+            Float::nth_root(float("8"), 0, &context),
+            Err(ArithmeticError::DivisionByZero)
+        );
+        assert_eq!(context.sqrt(float("-0")), Ok(float("0")));
+        // a root far below the last place is decided without building the radicand
+        let places = |places: isize, rounding: RoundingMode| {
+            Context::new(Precision::DecimalPlaces(places), rounding)
+        };
+        assert_eq!(
+            Float::nth_root(
+                float("100"),
+                2,
+                &places(-1_000_000_000_000, RoundingMode::HalfEven)
+            ),
+            Ok(float("0"))
+        );
+        assert_eq!(
+            Float::nth_root(
+                float("-100"),
+                3,
+                &places(-1_000_000_000_000, RoundingMode::Floor)
+            ),
+            Ok(Float::new(
+                Integer::from_isize(-1_000_000_000_000),
+                Integer::from(-1)
+            ))
+        );
+        // and a scale that cannot be written out is an error rather than a panic
+        assert_eq!(
+            Float::nth_root(
+                float("2"),
+                3,
+                &places(isize::MAX / 2, RoundingMode::HalfEven)
+            ),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            Float::sqrt(float("2"), &places(isize::MAX, RoundingMode::HalfEven)),
+            Err(ArithmeticError::Overflow)
+        );
+    }
+
+    /* Elementary functions */
+
+    #[test]
+    fn exact_elementary_results() {
+        let context: Context = significant(20, RoundingMode::HalfEven);
+        assert_eq!(
+            Float::pow(float("4"), float("0.5"), &context),
+            Ok(float("2"))
+        );
+        assert_eq!(
+            Float::pow(float("0.25"), float("-1.5"), &context),
+            Ok(float("8"))
+        );
+        assert_eq!(
+            Float::pow(float("-2"), float("3"), &context),
+            Ok(float("-8"))
+        );
+        assert_eq!(Float::log10(float("1000"), &context), Ok(float("3")));
+        assert_eq!(Float::log10(float("0.001"), &context), Ok(float("-3")));
+        assert_eq!(Float::log2(float("0.125"), &context), Ok(float("-3")));
+        assert_eq!(
+            Float::log(float("8"), float("4"), &context),
+            Ok(float("1.5"))
+        );
+        assert_eq!(Float::ln(float("1"), &context), Ok(float("0")));
+        assert_eq!(Float::exp(float("0"), &context), Ok(float("1")));
+        // an exact result still takes the scale of the context
+        let places: Context = Context::new(Precision::DecimalPlaces(3), RoundingMode::HalfEven);
+        assert_eq!(
+            Float::log10(float("1000"), &places).unwrap().to_string(),
+            "3.000"
+        );
+    }
+    #[test]
+    fn elementary_results_next_to_a_rounding_boundary() {
+        // e^38.518 = 5.3475 500000049735.. * 10^16, just above half a unit
+        let exp = |rounding: RoundingMode| Float::exp(float("38.518"), &significant(5, rounding));
+        assert_eq!(exp(RoundingMode::HalfEven), Ok(float("5.3476e16")));
+        assert_eq!(exp(RoundingMode::Truncate), Ok(float("5.3475e16")));
+        assert_eq!(exp(RoundingMode::Ceiling), Ok(float("5.3476e16")));
+        // ln 20328 = 9.91975452 500000001287.., also just above half a unit
+        let ln = |rounding: RoundingMode| Float::ln(float("20328"), &significant(9, rounding));
+        assert_eq!(ln(RoundingMode::HalfEven), Ok(float("9.91975453")));
+        assert_eq!(ln(RoundingMode::HalfUp), Ok(float("9.91975453")));
+        assert_eq!(ln(RoundingMode::Floor), Ok(float("9.91975452")));
+        let places: Context = Context::new(Precision::DecimalPlaces(8), RoundingMode::HalfEven);
+        assert_eq!(Float::ln(float("20328"), &places), Ok(float("9.91975453")));
+        // log10 153625 = 5.186461895894510 499999966410.., just below half a unit
+        let log10 =
+            |rounding: RoundingMode| Float::log10(float("153625"), &significant(16, rounding));
+        assert_eq!(log10(RoundingMode::HalfUp), Ok(float("5.186461895894510")));
+        assert_eq!(
+            log10(RoundingMode::HalfEven),
+            Ok(float("5.186461895894510"))
+        );
+        assert_eq!(log10(RoundingMode::Ceiling), Ok(float("5.186461895894511")));
+    }
+    #[test]
+    fn elementary_results_next_to_one() {
+        // e^n for a tiny n is 1 rounded toward the side of n, however far the digits of n are
+        let ceiling: Context = significant(34, RoundingMode::Ceiling);
+        let above_one: Float = float("1.000000000000000000000000000000001");
+        for n in ["1e-10000", "1e-100000", "1e-999999999"] {
+            assert_eq!(Float::exp(float(n), &ceiling), Ok(above_one.clone()));
+        }
+        let floor: Context = significant(34, RoundingMode::Floor);
+        assert_eq!(Float::exp(float("1e-999999999"), &floor), Ok(float("1")));
+        assert_eq!(
+            Float::exp(float("-1e-999999999"), &floor),
+            Ok(float("0.9999999999999999999999999999999999"))
+        );
+        assert_eq!(
+            Float::exp(float("-1e-10000"), &significant(34, RoundingMode::HalfEven)),
+            Ok(float("1"))
+        );
+        let places: Context = Context::new(Precision::DecimalPlaces(3), RoundingMode::Ceiling);
+        assert_eq!(
+            Float::exp(float("1e-100000"), &places).unwrap().to_string(),
+            "1.001"
+        );
+        // ln(1 + e) for a tiny e is e rounded from just below it
+        assert_eq!(
+            Float::ln(float("1.0000000000000000000000000000000000000001"), &floor),
+            Ok(float("9.999999999999999999999999999999999e-41"))
+        );
+        assert_eq!(
+            Float::ln(
+                float("1.0000000000000000000000000000000000000001"),
+                &ceiling
+            ),
+            Ok(float("1e-40"))
+        );
+        assert_eq!(
+            Float::ln(float("0.9999999999999999999999999999999999999999"), &floor),
+            Ok(float("-1.000000000000000000000000000000001e-40"))
+        );
+        // a result that never settles is given up on
+        let split = |_: usize| {
+            Ok(Some(vec![
+                (float("1"), float("3")),
+                (float("2"), float("3")),
+            ]))
+        };
+        assert_eq!(
+            Float::ziv_round(&ceiling, split),
+            Err(ArithmeticError::Overflow)
+        );
+    }
+    #[test]
+    fn large_exact_powers_under_directed_rounding() {
+        // 3^1400000 is too large for the quick exact path and sits on a boundary of every place
+        let exact: Float =
+            Float::from_integer(Integer::pow(Integer::from(3), Integer::from(1400000)).unwrap());
+        for rounding in [
+            RoundingMode::Floor,
+            RoundingMode::Ceiling,
+            RoundingMode::Truncate,
+        ] {
+            let places: Context = Context::new(Precision::DecimalPlaces(2), rounding);
+            assert_eq!(
+                Float::pow(float("3"), float("1400000"), &places),
+                Ok(exact.clone())
+            );
+        }
+    }
+    #[test]
+    fn elementary_function_errors() {
+        let context: Context = significant(10, RoundingMode::HalfEven);
+        assert_eq!(
+            Float::ln(float("0"), &context),
+            Err(ArithmeticError::NonPositiveLogarithm)
+        );
+        assert_eq!(
+            Float::ln(float("-1"), &context),
+            Err(ArithmeticError::NonPositiveLogarithm)
+        );
+        assert_eq!(
+            Float::log(float("10"), float("1"), &context),
+            Err(ArithmeticError::DivisionByZero)
+        );
+        assert_eq!(
+            Float::log(float("10"), float("1.000"), &context),
+            Err(ArithmeticError::DivisionByZero)
+        );
+        assert_eq!(
+            Float::log(float("10"), float("0"), &context),
+            Err(ArithmeticError::NonPositiveLogarithm)
+        );
+        assert_eq!(
+            Float::pow(float("0"), float("-1"), &context),
+            Err(ArithmeticError::DivisionByZero)
+        );
+        assert_eq!(
+            Float::pow(float("-8"), float("0.5"), &context),
+            Err(ArithmeticError::NegativeRoot)
+        );
+        assert_eq!(
+            context.ln(float("0")),
+            Err(ArithmeticError::NonPositiveLogarithm)
+        );
+        assert_eq!(
+            context.pow(float("0"), float("-1")),
+            Err(ArithmeticError::DivisionByZero)
+        );
+    }
+}
@@ -0,0 +1,1016 @@
+// 2024 (c) MaoHuPi
+// RuNeX/src/re_math/encoding.rs
+
+// binary and json encodings of Integer, Float and FormulaTypes
+//
+// binary, version 1
+//     [version: u8 = 1] [kind: u8] [body]
+//     kind: 0 Integer, 1 Float, 2 FormulaTypes
+//     varint: unsigned LEB128, 7 bits per byte with the lowest group first, never padded
+//     Integer body: varint(limb_count << 1 | sign), then limb_count varints, lowest limb first
+//         the limbs are 32 bits wide, zero has no limbs, the top limb is never 0 and zero is never negative
+//     Float body: the Integer body of point, then that of value (the number is value * 10^-point)
+//     FormulaTypes body: [tag: u8], then
+//         0 Integer body, 1 Float body, 2 varint(count) and count FormulaTypes bodies (Addition),
+//         3 one FormulaTypes body (Negative)
+//
+// json, version 1
+//     {"version": 1, "data": data}
+//     Integer data: a decimal string, "-123"
+//     Float data: a decimal string that keeps the scale, "1.50", "0.005", "5e3"
+//     FormulaTypes data: {"type": "integer", "value": "10"}, {"type": "float", "value": "1.5"},
+//         {"type": "addition", "elements": [data, ...]}, {"type": "negative", "element": data}
+//
+// decoding never panics, every malformed input gives a DecodeError with the byte offset
+// where it was noticed, and decode(encode(x)) gives back x exactly, scale of a Float included
+
+use std::fmt;
+
+pub const ENCODING_VERSION: u8 = 1;
+// nesting deeper than this is rejected instead of overflowing the stack
+pub const MAX_DEPTH: usize = 512;
+// json containers allowed around a formula MAX_DEPTH levels deep: the envelope, an object and an
+// elements array per level, and the value string of the leaf
+const JSON_MAX_DEPTH: usize = 2 * MAX_DEPTH + 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    UnexpectedEnd,      // the input stops in the middle of a value
+    UnsupportedVersion, // written by a newer (or unknown) version
+    WrongKind,          // holds another type than the one asked for
+    InvalidTag,         // an unknown formula tag or json type
+    Overflow,           // a varint or limb that does not fit
+    NonCanonical,       // padded varints, leading zero limbs, negative zero
+    TrailingData,       // bytes left after the value
+    TooDeep,            // nested more than MAX_DEPTH levels
+    InvalidJson,        // not json at all
+    InvalidValue,       // json of the wrong shape, or a malformed number
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    kind: DecodeErrorKind,
+    position: usize, // byte offset into the input
+}
+#[allow(dead_code)]
+impl DecodeError {
+    pub fn new(kind: DecodeErrorKind, position: usize) -> Self {
+        Self {
+            kind: kind,
+            position: position,
+        }
+    }
+    pub fn kind(self: &Self) -> DecodeErrorKind {
+        self.kind
+    }
+    pub fn position(self: &Self) -> usize {
+        self.position
+    }
+}
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message: &str = match self.kind {
+            DecodeErrorKind::UnexpectedEnd => "unexpected end of input",
+            DecodeErrorKind::UnsupportedVersion => "unsupported encoding version",
+            DecodeErrorKind::WrongKind => "encoded value has another type",
+            DecodeErrorKind::InvalidTag => "unknown tag",
+            DecodeErrorKind::Overflow => "number too large",
+            DecodeErrorKind::NonCanonical => "non-canonical encoding",
+            DecodeErrorKind::TrailingData => "trailing data",
+            DecodeErrorKind::TooDeep => "nested too deeply",
+            DecodeErrorKind::InvalidJson => "invalid json",
+            DecodeErrorKind::InvalidValue => "invalid value",
+        };
+        write!(f, "{} at byte {}", message, self.position)
+    }
+}
+impl std::error::Error for DecodeError {}
+
+/* binary */
+
+pub fn write_varint(out: &mut Vec<u8>, value: u64) {
+    let mut value: u64 = value;
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes: bytes,
+            position: 0,
+        }
+    }
+    pub fn position(self: &Self) -> usize {
+        self.position
+    }
+    pub fn remaining(self: &Self) -> usize {
+        self.bytes.len() - self.position
+    }
+    pub fn error(self: &Self, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError::new(kind, self.position)
+    }
+    pub fn read_u8(self: &mut Self) -> Result<u8, DecodeError> {
+        match self.bytes.get(self.position) {
+            Some(&byte) => {
+                self.position += 1;
+                Ok(byte)
+            }
+            None => Err(self.error(DecodeErrorKind::UnexpectedEnd)),
+        }
+    }
+    pub fn read_varint(self: &mut Self) -> Result<u64, DecodeError> {
+        let start: usize = self.position;
+        let mut value: u64 = 0;
+        let mut shift: u32 = 0;
+        loop {
+            let byte: u8 = self.read_u8()?;
+            let group: u64 = (byte & 0x7f) as u64;
+            if shift >= u64::BITS || (group << shift) >> shift != group {
+                return Err(DecodeError::new(DecodeErrorKind::Overflow, start));
+            }
+            value |= group << shift;
+            if byte & 0x80 == 0 {
+                // a zero last group (other than a lone 0) is padding
+                if byte == 0 && shift > 0 {
+                    return Err(DecodeError::new(DecodeErrorKind::NonCanonical, start));
+                }
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+}
+
+/* json */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String), // kept as written, so no precision is lost
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+#[derive(Clone, Debug, PartialEq)]
+pub struct Json {
+    pub value: JsonValue,
+    pub position: usize, // byte offset of the value in the parsed text
+}
+
+#[allow(dead_code)]
+impl Json {
+    pub fn new(value: JsonValue) -> Self {
+        Self {
+            value: value,
+            position: 0,
+        }
+    }
+    pub fn string(text: String) -> Self {
+        Self::new(JsonValue::String(text))
+    }
+    pub fn object(fields: Vec<(&str, Json)>) -> Self {
+        Self::new(JsonValue::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        ))
+    }
+
+    pub fn error(self: &Self) -> DecodeError {
+        DecodeError::new(DecodeErrorKind::InvalidValue, self.position)
+    }
+    pub fn get(self: &Self, key: &str) -> Result<&Json, DecodeError> {
+        match &self.value {
+            JsonValue::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .ok_or(self.error()),
+            _ => Err(self.error()),
+        }
+    }
+    pub fn as_str(self: &Self) -> Result<&str, DecodeError> {
+        match &self.value {
+            JsonValue::String(text) => Ok(text),
+            _ => Err(self.error()),
+        }
+    }
+    pub fn as_array(self: &Self) -> Result<&[Json], DecodeError> {
+        match &self.value {
+            JsonValue::Array(items) => Ok(items),
+            _ => Err(self.error()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, DecodeError> {
+        let bytes: &[u8] = text.as_bytes();
+        let invalid = |position: usize| DecodeError::new(DecodeErrorKind::InvalidJson, position);
+        let at = |value: JsonValue, position: usize| Self {
+            value: value,
+            position: position,
+        };
+        // the open containers, kept on the heap so deep input cannot overflow the call stack
+        let mut open: Vec<Container> = Vec::new();
+        let mut position: usize = skip_whitespace(bytes, 0);
+        loop {
+            if open.len() > JSON_MAX_DEPTH {
+                return Err(DecodeError::new(DecodeErrorKind::TooDeep, position));
+            }
+            // read a value, or open a container and go on with its first element
+            let mut json: Self = match bytes.get(position) {
+                None => return Err(DecodeError::new(DecodeErrorKind::UnexpectedEnd, position)),
+                Some(b'n') if bytes[position..].starts_with(b"null") => {
+                    position += 4;
+                    at(JsonValue::Null, position - 4)
+                }
+                Some(b't') if bytes[position..].starts_with(b"true") => {
+                    position += 4;
+                    at(JsonValue::Bool(true), position - 4)
+                }
+                Some(b'f') if bytes[position..].starts_with(b"false") => {
+                    position += 5;
+                    at(JsonValue::Bool(false), position - 5)
+                }
+                Some(b'"') => {
+                    let (text, next) = parse_string(bytes, position)?;
+                    let json: Self = at(JsonValue::String(text), position);
+                    position = next;
+                    json
+                }
+                Some(b'[') => {
+                    let next: usize = skip_whitespace(bytes, position + 1);
+                    if bytes.get(next) == Some(&b']') {
+                        let json: Self = at(JsonValue::Array(Vec::new()), position);
+                        position = next + 1;
+                        json
+                    } else {
+                        open.push(Container::Array(position, Vec::new()));
+                        position = next;
+                        continue;
+                    }
+                }
+                Some(b'{') => {
+                    let next: usize = skip_whitespace(bytes, position + 1);
+                    if bytes.get(next) == Some(&b'}') {
+                        let json: Self = at(JsonValue::Object(Vec::new()), position);
+                        position = next + 1;
+                        json
+                    } else {
+                        let (key, next) = parse_key(bytes, next)?;
+                        open.push(Container::Object(position, Vec::new(), key));
+                        position = next;
+                        continue;
+                    }
+                }
+                Some(b'-' | b'0'..=b'9') => {
+                    let next: usize = parse_number(bytes, position).ok_or(invalid(position))?;
+                    let text: String = String::from_utf8_lossy(&bytes[position..next]).into_owned();
+                    let json: Self = at(JsonValue::Number(text), position);
+                    position = next;
+                    json
+                }
+                Some(_) => return Err(invalid(position)),
+            };
+            // hand the value to its container, closing every container that ends after it
+            loop {
+                position = skip_whitespace(bytes, position);
+                match open.last_mut() {
+                    None => {
+                        if position < bytes.len() {
+                            return Err(DecodeError::new(DecodeErrorKind::TrailingData, position));
+                        }
+                        return Ok(json);
+                    }
+                    Some(Container::Array(start, items)) => {
+                        items.push(json);
+                        match bytes.get(position) {
+                            Some(b',') => {
+                                position = skip_whitespace(bytes, position + 1);
+                                break;
+                            }
+                            Some(b']') => {
+                                position += 1;
+                                json = at(JsonValue::Array(std::mem::take(items)), *start);
+                                open.pop();
+                            }
+                            Some(_) => return Err(invalid(position)),
+                            None => {
+                                return Err(DecodeError::new(
+                                    DecodeErrorKind::UnexpectedEnd,
+                                    position,
+                                ))
+                            }
+                        }
+                    }
+                    Some(Container::Object(start, fields, key)) => {
+                        fields.push((std::mem::take(key), json));
+                        match bytes.get(position) {
+                            Some(b',') => {
+                                let next: usize = skip_whitespace(bytes, position + 1);
+                                let (next_key, next) = parse_key(bytes, next)?;
+                                *key = next_key;
+                                position = next;
+                                break;
+                            }
+                            Some(b'}') => {
+                                position += 1;
+                                json = at(JsonValue::Object(std::mem::take(fields)), *start);
+                                open.pop();
+                            }
+                            Some(_) => return Err(invalid(position)),
+                            None => {
+                                return Err(DecodeError::new(
+                                    DecodeErrorKind::UnexpectedEnd,
+                                    position,
+                                ))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    fn write(self: &Self, out: &mut String) {
+        match &self.value {
+            JsonValue::Null => out.push_str("null"),
+            JsonValue::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            JsonValue::Number(text) => out.push_str(text),
+            JsonValue::String(text) => write_string(out, text),
+            JsonValue::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    item.write(out);
+                }
+                out.push(']');
+            }
+            JsonValue::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_string(out, key);
+                    out.push(':');
+                    value.write(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+}
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out: String = String::new();
+        self.write(&mut out);
+        f.write_str(&out)
+    }
+}
+
+// a json container whose closing bracket has not been read yet, with its position
+enum Container {
+    Array(usize, Vec<Json>),
+    Object(usize, Vec<(String, Json)>, String), // with the key of the value being read
+}
+
+// reads `"key":` and returns the key and the position of its value
+fn parse_key(bytes: &[u8], position: usize) -> Result<(String, usize), DecodeError> {
+    if bytes.get(position) != Some(&b'"') {
+        return Err(DecodeError::new(DecodeErrorKind::InvalidJson, position));
+    }
+    let (key, after) = parse_string(bytes, position)?;
+    let next: usize = skip_whitespace(bytes, after);
+    if bytes.get(next) != Some(&b':') {
+        return Err(DecodeError::new(DecodeErrorKind::InvalidJson, next));
+    }
+    Ok((key, skip_whitespace(bytes, next + 1)))
+}
+
+fn skip_whitespace(bytes: &[u8], position: usize) -> usize {
+    let mut position: usize = position;
+    while matches!(bytes.get(position), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+        position += 1;
+    }
+    position
+}
+fn parse_number(bytes: &[u8], position: usize) -> Option<usize> {
+    // the position after a json number, -?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?
+    let digits = |position: usize| -> usize {
+        let mut end: usize = position;
+        while bytes.get(end).is_some_and(|c| c.is_ascii_digit()) {
+            end += 1;
+        }
+        end
+    };
+    let mut position: usize = position;
+    if bytes.get(position) == Some(&b'-') {
+        position += 1;
+    }
+    match bytes.get(position) {
+        Some(b'0') => position += 1,
+        Some(b'1'..=b'9') => position = digits(position),
+        _ => return None,
+    }
+    if bytes.get(position) == Some(&b'.') {
+        let end: usize = digits(position + 1);
+        if end == position + 1 {
+            return None;
+        }
+        position = end;
+    }
+    if matches!(bytes.get(position), Some(b'e' | b'E')) {
+        position += 1;
+        if matches!(bytes.get(position), Some(b'+' | b'-')) {
+            position += 1;
+        }
+        let end: usize = digits(position);
+        if end == position {
+            return None;
+        }
+        position = end;
+    }
+    Some(position)
+}
+fn parse_string(bytes: &[u8], position: usize) -> Result<(String, usize), DecodeError> {
+    // bytes[position] is the opening quote
+    let invalid = |position: usize| DecodeError::new(DecodeErrorKind::InvalidJson, position);
+    let end = |position: usize| DecodeError::new(DecodeErrorKind::UnexpectedEnd, position);
+    let read_hex = |position: usize| -> Result<u32, DecodeError> {
+        let hex: &[u8] = bytes.get(position..position + 4).ok_or(end(position))?;
+        let hex: &str = std::str::from_utf8(hex).map_err(|_| invalid(position))?;
+        u32::from_str_radix(hex, 16).map_err(|_| invalid(position))
+    };
+    let mut text: Vec<u8> = Vec::new();
+    let mut position: usize = position + 1;
+    loop {
+        match bytes.get(position) {
+            None => return Err(end(position)),
+            Some(b'"') => break,
+            Some(b'\\') => {
+                let escaped: char = match bytes.get(position + 1) {
+                    None => return Err(end(position + 1)),
+                    Some(b'"') => '"',
+                    Some(b'\\') => '\\',
+                    Some(b'/') => '/',
+                    Some(b'b') => '\u{8}',
+                    Some(b'f') => '\u{c}',
+                    Some(b'n') => '\n',
+                    Some(b'r') => '\r',
+                    Some(b't') => '\t',
+                    Some(b'u') => {
+                        let mut code: u32 = read_hex(position + 2)?;
+                        if (0xd800..0xdc00).contains(&code) {
+                            // a surrogate pair
+                            if bytes.get(position + 6..position + 8) != Some(b"\\u") {
+                                return Err(invalid(position));
+                            }
+                            let low: u32 = read_hex(position + 8)?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return Err(invalid(position));
+                            }
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            position += 6;
+                        }
+                        position += 4;
+                        char::from_u32(code).ok_or(invalid(position))?
+                    }
+                    Some(_) => return Err(invalid(position)),
+                };
+                let mut buffer: [u8; 4] = [0; 4];
+                text.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+                position += 2;
+            }
+            Some(&c) if c < 0x20 => return Err(invalid(position)),
+            Some(&c) => {
+                text.push(c);
+                position += 1;
+            }
+        }
+    }
+    // the input is a &str, so the bytes between the quotes are still utf-8
+    Ok((
+        String::from_utf8(text).map_err(|_| invalid(position))?,
+        position + 1,
+    ))
+}
+fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/* Encoding */
+
+// implemented next to each type, the provided methods add the version header
+#[allow(dead_code)]
+pub trait Encoding: Sized {
+    const KIND: u8;
+    fn encode_body(self: &Self, out: &mut Vec<u8>);
+    fn decode_body(reader: &mut Reader) -> Result<Self, DecodeError>;
+    fn to_json_value(self: &Self) -> Json;
+    fn from_json_value(json: &Json) -> Result<Self, DecodeError>;
+
+    fn to_bytes(self: &Self) -> Vec<u8> {
+        let mut out: Vec<u8> = vec![ENCODING_VERSION, Self::KIND];
+        self.encode_body(&mut out);
+        out
+    }
+    fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader: Reader = Reader::new(bytes);
+        if reader.read_u8()? != ENCODING_VERSION {
+            return Err(DecodeError::new(DecodeErrorKind::UnsupportedVersion, 0));
+        }
+        if reader.read_u8()? != Self::KIND {
+            return Err(DecodeError::new(DecodeErrorKind::WrongKind, 1));
+        }
+        let result: Self = Self::decode_body(&mut reader)?;
+        if reader.remaining() > 0 {
+            return Err(reader.error(DecodeErrorKind::TrailingData));
+        }
+        Ok(result)
+    }
+    fn to_json_document(self: &Self) -> Json {
+        Json::object(vec![
+            (
+                "version",
+                Json::new(JsonValue::Number(ENCODING_VERSION.to_string())),
+            ),
+            ("data", self.to_json_value()),
+        ])
+    }
+    fn from_json_document(json: &Json) -> Result<Self, DecodeError> {
+        let version: &Json = json.get("version")?;
+        if version.value != JsonValue::Number(ENCODING_VERSION.to_string()) {
+            return Err(DecodeError::new(
+                DecodeErrorKind::UnsupportedVersion,
+                version.position,
+            ));
+        }
+        Self::from_json_value(json.get("data")?)
+    }
+    fn to_json(self: &Self) -> String {
+        self.to_json_document().to_string()
+    }
+    fn from_json(text: &str) -> Result<Self, DecodeError> {
+        Self::from_json_document(&Json::parse(text)?)
+    }
+}
+
+/* serde */
+
+// human readable formats get the json document, the others the binary encoding
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::{Encoding, Json, JsonValue};
+    use crate::re_math::advanced_type::FormulaTypes;
+    use crate::re_math::basic_type::{Float, Integer};
+    use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+    use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+    use std::fmt;
+
+    impl Serialize for Json {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match &self.value {
+                JsonValue::Null => serializer.serialize_unit(),
+                JsonValue::Bool(value) => serializer.serialize_bool(*value),
+                JsonValue::Number(text) => match text.parse::<u64>() {
+                    Ok(value) => serializer.serialize_u64(value),
+                    Err(_) => serializer.serialize_str(text),
+                },
+                JsonValue::String(text) => serializer.serialize_str(text),
+                JsonValue::Array(items) => {
+                    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                    for item in items {
+                        seq.serialize_element(item)?;
+                    }
+                    seq.end()
+                }
+                JsonValue::Object(fields) => {
+                    let mut map = serializer.serialize_map(Some(fields.len()))?;
+                    for (key, value) in fields {
+                        map.serialize_entry(key, value)?;
+                    }
+                    map.end()
+                }
+            }
+        }
+    }
+
+    struct JsonVisitor;
+    impl<'de> Visitor<'de> for JsonVisitor {
+        type Value = Json;
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a json value")
+        }
+        fn visit_unit<E: de::Error>(self) -> Result<Json, E> {
+            Ok(Json::new(JsonValue::Null))
+        }
+        fn visit_bool<E: de::Error>(self, value: bool) -> Result<Json, E> {
+            Ok(Json::new(JsonValue::Bool(value)))
+        }
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Json, E> {
+            Ok(Json::new(JsonValue::Number(value.to_string())))
+        }
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Json, E> {
+            Ok(Json::new(JsonValue::Number(value.to_string())))
+        }
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Json, E> {
+            Ok(Json::new(JsonValue::Number(value.to_string())))
+        }
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Json, E> {
+            Ok(Json::string(value.to_string()))
+        }
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error> {
+            let mut items: Vec<Json> = Vec::new();
+            while let Some(item) = seq.next_element()? {
+                items.push(item);
+            }
+            Ok(Json::new(JsonValue::Array(items)))
+        }
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+            let mut fields: Vec<(String, Json)> = Vec::new();
+            while let Some(field) = map.next_entry()? {
+                fields.push(field);
+            }
+            Ok(Json::new(JsonValue::Object(fields)))
+        }
+    }
+    impl<'de> Deserialize<'de> for Json {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Json, D::Error> {
+            deserializer.deserialize_any(JsonVisitor)
+        }
+    }
+
+    struct BytesVisitor;
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("encoded bytes")
+        }
+        fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(value.to_vec())
+        }
+        fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(value)
+        }
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes: Vec<u8> = Vec::new();
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+
+    macro_rules! impl_serde_for_encoding {
+        ($($type:ty),*) => {
+            $(
+                impl Serialize for $type {
+                    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                        if serializer.is_human_readable() {
+                            self.to_json_document().serialize(serializer)
+                        } else {
+                            serializer.serialize_bytes(&self.to_bytes())
+                        }
+                    }
+                }
+                impl<'de> Deserialize<'de> for $type {
+                    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                        if deserializer.is_human_readable() {
+                            let json: Json = Json::deserialize(deserializer)?;
+                            <$type>::from_json_document(&json).map_err(de::Error::custom)
+                        } else {
+                            let bytes: Vec<u8> = deserializer.deserialize_bytes(BytesVisitor)?;
+                            <$type>::from_bytes(&bytes).map_err(de::Error::custom)
+                        }
+                    }
+                }
+            )*
+        };
+    }
+    impl_serde_for_encoding!(Integer, Float, FormulaTypes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::re_math::advanced_type::{Addition, FormulaTypes, Negative};
+    use crate::re_math::basic_type::{Float, Integer};
+
+    fn integers() -> Vec<Integer> {
+        [
+            "0",
+            "1",
+            "-1",
+            "4294967295",
+            "-4294967296",
+            "123456789012345678901234567890",
+        ]
+        .iter()
+        .map(|text| text.parse::<Integer>().unwrap())
+        .collect()
+    }
+    fn floats() -> Vec<Float> {
+        [
+            "0", "0.0", "1.50", "-0.005", "5e3", "-12.345", "1e-30", "-7e40",
+        ]
+        .iter()
+        .map(|text| text.parse::<Float>().unwrap())
+        .collect()
+    }
+    fn formula() -> FormulaTypes {
+        let mut elements: Vec<Box<FormulaTypes>> = Vec::new();
+        for n in integers() {
+            elements.push(Box::new(FormulaTypes::Integer(n)));
+        }
+        for n in floats() {
+            elements.push(Box::new(FormulaTypes::Negative(Negative::new(Box::new(
+                FormulaTypes::Float(n),
+            )))));
+        }
+        FormulaTypes::Addition(Addition::new(elements))
+    }
+    fn nested_negatives(count: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![ENCODING_VERSION, FormulaTypes::KIND];
+        bytes.extend(std::iter::repeat_n(3, count));
+        // an Integer zero at the bottom
+        bytes.extend([0, 0]);
+        bytes
+    }
+    fn decode_error<T>(result: Result<T, DecodeError>) -> DecodeError {
+        match result {
+            Ok(_) => panic!("decoded malformed input"),
+            Err(error) => error,
+        }
+    }
+    fn kind<T>(result: Result<T, DecodeError>) -> DecodeErrorKind {
+        decode_error(result).kind()
+    }
+
+    /* round trips */
+
+    #[test]
+    fn integer_round_trips() {
+        for n in integers() {
+            assert_eq!(Integer::from_bytes(&n.to_bytes()).unwrap(), n);
+            assert_eq!(Integer::from_json(&n.to_json()).unwrap(), n);
+        }
+    }
+    #[test]
+    fn float_round_trips_keep_the_scale() {
+        for n in floats() {
+            let decoded: Float = Float::from_bytes(&n.to_bytes()).unwrap();
+            assert_eq!(decoded.to_bytes(), n.to_bytes());
+            assert_eq!(decoded.to_string(), n.to_string());
+            let decoded: Float = Float::from_json(&n.to_json()).unwrap();
+            assert_eq!(decoded.to_bytes(), n.to_bytes());
+        }
+        assert_eq!(
+            "1.50".parse::<Float>().unwrap().to_json(),
+            r#"{"version":1,"data":"1.50"}"#
+        );
+    }
+    #[test]
+    fn float_round_trips_beyond_the_parsed_range() {
+        // a product can take its point past anything a parsed string holds
+        let tiny: Float = Float::mul(
+            "1e-999999999".parse::<Float>().unwrap(),
+            "1e-5".parse::<Float>().unwrap(),
+        );
+        let huge: Float = Float::mul(
+            "-3e999999999".parse::<Float>().unwrap(),
+            "1e999999999".parse::<Float>().unwrap(),
+        );
+        for n in [tiny, huge] {
+            assert_eq!(
+                Float::from_bytes(&n.to_bytes()).unwrap().to_bytes(),
+                n.to_bytes()
+            );
+            assert_eq!(
+                Float::from_json(&n.to_json()).unwrap().to_bytes(),
+                n.to_bytes()
+            );
+        }
+    }
+    #[test]
+    fn formula_round_trips() {
+        let formula: FormulaTypes = formula();
+        let bytes: Vec<u8> = formula.to_bytes();
+        assert_eq!(FormulaTypes::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+        let json: String = formula.to_json();
+        assert_eq!(FormulaTypes::from_json(&json).unwrap().to_json(), json);
+    }
+
+    /* serde */
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_matches_the_json_encoding() {
+        // a human readable serializer gets the same document as to_json
+        for n in integers() {
+            let text: String = serde_json::to_string(&n).unwrap();
+            assert_eq!(text, n.to_json());
+            assert_eq!(serde_json::from_str::<Integer>(&text).unwrap(), n);
+        }
+        for n in floats() {
+            let text: String = serde_json::to_string(&n).unwrap();
+            assert_eq!(text, n.to_json());
+            let decoded: Float = serde_json::from_str(&text).unwrap();
+            assert_eq!(decoded.to_bytes(), n.to_bytes());
+        }
+        let formula: FormulaTypes = formula();
+        let text: String = serde_json::to_string(&formula).unwrap();
+        assert_eq!(text, formula.to_json());
+        let decoded: FormulaTypes = serde_json::from_str(&text).unwrap();
+        assert_eq!(decoded.to_bytes(), formula.to_bytes());
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_binary_round_trips() {
+        // a binary serializer gets the versioned bytes of to_bytes
+        for n in integers() {
+            let bytes: Vec<u8> = postcard::to_allocvec(&n).unwrap();
+            assert_eq!(postcard::from_bytes::<Integer>(&bytes).unwrap(), n);
+        }
+        for n in floats() {
+            let bytes: Vec<u8> = postcard::to_allocvec(&n).unwrap();
+            let decoded: Float = postcard::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.to_bytes(), n.to_bytes());
+        }
+        let formula: FormulaTypes = formula();
+        let bytes: Vec<u8> = postcard::to_allocvec(&formula).unwrap();
+        assert!(bytes.ends_with(&formula.to_bytes()));
+        let decoded: FormulaTypes = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), formula.to_bytes());
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_reports_decode_errors() {
+        assert!(serde_json::from_str::<Integer>(r#"{"version":1,"data":"12x"}"#).is_err());
+        assert!(serde_json::from_str::<Float>(r#"{"version":2,"data":"1.5"}"#).is_err());
+        assert!(serde_json::from_str::<FormulaTypes>(r#"{"version":1,"data":"1"}"#).is_err());
+        // the right bytes for another kind
+        let bytes: Vec<u8> = postcard::to_allocvec(&integers()[3]).unwrap();
+        assert!(postcard::from_bytes::<Float>(&bytes).is_err());
+        assert!(postcard::from_bytes::<Integer>(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn deepest_formulas_round_trip_through_json() {
+        // whatever the binary decoder accepts, its json encoding reads back as well
+        let mut additions: FormulaTypes = FormulaTypes::Float("-1.5".parse::<Float>().unwrap());
+        for _ in 0..MAX_DEPTH {
+            additions = FormulaTypes::Addition(Addition::new(vec![Box::new(additions)]));
+        }
+        let deepest_negatives: FormulaTypes =
+            FormulaTypes::from_bytes(&nested_negatives(MAX_DEPTH)).unwrap();
+        for formula in [additions, deepest_negatives] {
+            let bytes: Vec<u8> = formula.to_bytes();
+            let decoded: FormulaTypes = FormulaTypes::from_bytes(&bytes).unwrap();
+            let json: String = decoded.to_json();
+            assert_eq!(FormulaTypes::from_json(&json).unwrap().to_bytes(), bytes);
+        }
+    }
+
+    /* malformed input */
+
+    #[test]
+    fn truncated_input() {
+        let bytes: Vec<u8> = formula().to_bytes();
+        for end in 0..bytes.len() {
+            let result: Result<FormulaTypes, DecodeError> = FormulaTypes::from_bytes(&bytes[..end]);
+            assert_eq!(
+                kind(result),
+                DecodeErrorKind::UnexpectedEnd,
+                "{} bytes",
+                end
+            );
+        }
+        let json: String = formula().to_json();
+        for end in 0..json.len() {
+            assert!(
+                FormulaTypes::from_json(&json[..end]).is_err(),
+                "{} bytes",
+                end
+            );
+        }
+    }
+    #[test]
+    fn oversized_varint() {
+        // eleven groups hold more than 64 bits
+        let mut bytes: Vec<u8> = vec![ENCODING_VERSION, Integer::KIND];
+        bytes.extend([0xff; 10]);
+        bytes.push(0x01);
+        let error: DecodeError = Integer::from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            (error.kind(), error.position()),
+            (DecodeErrorKind::Overflow, 2)
+        );
+        // a limb is 32 bits wide
+        let mut bytes: Vec<u8> = vec![ENCODING_VERSION, Integer::KIND, 1 << 1];
+        write_varint(&mut bytes, 1 << 32);
+        let error: DecodeError = Integer::from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            (error.kind(), error.position()),
+            (DecodeErrorKind::Overflow, 3)
+        );
+        // and padding is rejected
+        let bytes: Vec<u8> = vec![ENCODING_VERSION, Integer::KIND, 0x82, 0x00, 0x05];
+        assert_eq!(
+            kind(Integer::from_bytes(&bytes)),
+            DecodeErrorKind::NonCanonical
+        );
+    }
+    #[test]
+    fn non_canonical_values() {
+        // a leading zero limb and negative zero
+        for body in [vec![0x02, 0x00], vec![0x01]] {
+            let mut bytes: Vec<u8> = vec![ENCODING_VERSION, Integer::KIND];
+            bytes.extend(body);
+            let error: DecodeError = Integer::from_bytes(&bytes).unwrap_err();
+            assert_eq!(
+                (error.kind(), error.position()),
+                (DecodeErrorKind::NonCanonical, 2)
+            );
+        }
+        // zero with a point other than 0
+        let bytes: Vec<u8> = vec![ENCODING_VERSION, Float::KIND, 0x02, 0x02, 0x00];
+        let error: DecodeError = Float::from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            (error.kind(), error.position()),
+            (DecodeErrorKind::NonCanonical, 2)
+        );
+        let bytes: Vec<u8> = vec![ENCODING_VERSION, Float::KIND, 0x00, 0x00];
+        assert_eq!(Float::from_bytes(&bytes).unwrap(), Float::zero());
+    }
+    #[test]
+    fn over_long_limb_count() {
+        // the count is checked against the input before anything is allocated
+        for count in [3, 1 << 40, u64::MAX >> 1] {
+            let mut bytes: Vec<u8> = vec![ENCODING_VERSION, Integer::KIND];
+            write_varint(&mut bytes, count << 1);
+            bytes.extend([1, 2]);
+            let error: DecodeError = Integer::from_bytes(&bytes).unwrap_err();
+            assert_eq!(error.kind(), DecodeErrorKind::UnexpectedEnd);
+            assert_eq!(error.position(), bytes.len());
+        }
+        let mut bytes: Vec<u8> = vec![ENCODING_VERSION, FormulaTypes::KIND, 2];
+        write_varint(&mut bytes, u64::MAX);
+        assert_eq!(
+            kind(FormulaTypes::from_bytes(&bytes)),
+            DecodeErrorKind::UnexpectedEnd
+        );
+    }
+    #[test]
+    fn nesting_up_to_max_depth() {
+        assert!(FormulaTypes::from_bytes(&nested_negatives(MAX_DEPTH)).is_ok());
+        let error: DecodeError =
+            decode_error(FormulaTypes::from_bytes(&nested_negatives(MAX_DEPTH + 1)));
+        assert_eq!(
+            (error.kind(), error.position()),
+            (DecodeErrorKind::TooDeep, MAX_DEPTH + 3)
+        );
+        // far deeper input stops at the same depth instead of overflowing the stack
+        assert_eq!(
+            kind(FormulaTypes::from_bytes(&nested_negatives(1_000_000))),
+            DecodeErrorKind::TooDeep
+        );
+        let negatives = |count: usize| {
+            let mut json: String = r#"{"version":1,"data":"#.to_string();
+            json.push_str(&r#"{"type":"negative","element":"#.repeat(count));
+            json.push_str(r#"{"type":"integer","value":"0"}"#);
+            json.push_str(&"}".repeat(count + 1));
+            json
+        };
+        assert!(FormulaTypes::from_json(&negatives(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            kind(FormulaTypes::from_json(&negatives(MAX_DEPTH + 1))),
+            DecodeErrorKind::TooDeep
+        );
+        assert_eq!(
+            kind(Json::parse(&"[".repeat(1_000_000))),
+            DecodeErrorKind::TooDeep
+        );
+    }
+}
@@ -3,4 +3,5 @@
 
 // use crate::basic_type::Radix10Uint;
 pub mod basic_type;
-pub mod advanced_type;
\ No newline at end of file
+pub mod advanced_type;
+pub mod encoding;